@@ -1,3 +0,0 @@
-use std::marker::PhantomData;
-use num::Float;
-
@@ -0,0 +1,549 @@
+#![allow(dead_code)]
+
+use crate::grid::Grid2D;
+pub use crate::grid::Side;
+use crate::utils::geometry::Cartesian2D;
+use super::bounding_size;
+
+// elliptic (winslow / thompson-thames-mastin) smoothing stuff //
+// the boundary nodes of the starting grid are held fixed and the interior is relaxed
+// towards a solution of
+//     alpha x_xixi - 2 beta x_xieta + gamma x_etaeta = 0
+//     alpha y_xixi - 2 beta y_xieta + gamma y_etaeta = 0
+// with alpha = x_eta^2 + y_eta^2, beta = x_xi x_eta + y_xi y_eta, gamma = x_xi^2 + y_xi^2.
+// wall controls add the poisson source terms j^2 (p x_xi + q x_eta) to the left hand side.
+// on a periodic grid the seam is solved like any other interior line, with the last column
+// kept as a copy of the first
+pub struct EllipticGenerator {
+    grid: Grid2D,
+    tolerance: f64,
+    max_iterations: usize,
+    relaxation: f64,
+    residuals: Vec<f64>,
+    controls: Vec<WallControl>,
+    control_relaxation: f64,
+}
+
+// sorenson / steger control of one boundary: grid lines leave the boundary orthogonally
+// with the given first cell spacing. p and q are found at the boundary and decay into
+// the interior as exp(-decay * index distance from the boundary)
+#[derive(Debug, Clone)]
+pub struct WallControl {
+    side: Side,
+    spacing: f64,
+    decay: f64,
+    p: Vec<f64>,
+    q: Vec<f64>,
+}
+
+impl EllipticGenerator {
+    pub fn new(grid: Grid2D, tolerance: f64) -> Result<Self, &'static str> {
+        if !grid.is_complete() {
+            return Err("starting grid must have every point set");
+        }
+        if grid.nx() < 3 || grid.ny() < 3 {
+            return Err("starting grid must have at least one interior point");
+        }
+        if tolerance.is_nan() || tolerance <= 0.0 {
+            return Err("tolerance must be positive");
+        }
+
+        Ok(EllipticGenerator {
+            grid,
+            tolerance,
+            max_iterations: 10_000,
+            relaxation: 1.0,
+            residuals: Vec::new(),
+            controls: Vec::new(),
+            control_relaxation: 0.1,
+        })
+    }
+
+    // asks for orthogonal grid lines and a first cell spacing at one side of the grid,
+    // replacing any control already set on that side
+    pub fn set_wall_control(&mut self, side: Side, spacing: f64, decay: f64) -> Result<(), &'static str> {
+        if spacing <= 0.0 {
+            return Err("wall spacing must be positive");
+        }
+        if decay <= 0.0 {
+            return Err("control function decay must be positive");
+        }
+
+        if self.grid.is_periodic() && matches!(side, Side::Left | Side::Right) {
+            return Err("periodic grid has no left or right boundary to control");
+        }
+
+        let n = self.grid.side_len(side);
+        self.controls.retain(|control| control.side != side);
+        self.controls.push(WallControl { side, spacing, decay, p: vec![0.0; n], q: vec![0.0; n] });
+        Ok(())
+    }
+
+    // under-relaxation applied to the boundary values of p and q each sweep
+    pub fn set_control_relaxation(&mut self, relaxation: f64) -> Result<(), &'static str> {
+        if relaxation <= 0.0 || relaxation > 1.0 {
+            return Err("control relaxation factor must be between 0 and 1");
+        }
+        self.control_relaxation = relaxation;
+        Ok(())
+    }
+
+    pub fn wall_controls(&self) -> &[WallControl] {
+        &self.controls
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    // over-relaxation factor for the point sor sweeps, must be in (0, 2)
+    pub fn set_relaxation(&mut self, relaxation: f64) -> Result<(), &'static str> {
+        if relaxation <= 0.0 || relaxation >= 2.0 {
+            return Err("relaxation factor must be between 0 and 2");
+        }
+        self.relaxation = relaxation;
+        Ok(())
+    }
+
+    pub fn grid(&self) -> &Grid2D {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid2D {
+        self.grid
+    }
+
+    // residual after each sweep: the largest winslow residual over the interior, divided by
+    // 2 (alpha + gamma) at each node and by the grid size, evaluated on the swept grid
+    pub fn residuals(&self) -> &[f64] {
+        &self.residuals
+    }
+
+    // runs sor sweeps until the residual drops below the tolerance, returning the number
+    // of sweeps taken. the grid is updated in place even if the solve does not converge
+    pub fn solve(&mut self) -> Result<usize, &'static str> {
+        let nx = self.grid.nx();
+        let ny = self.grid.ny();
+        let mut x: Vec<f64> = self.grid.points().iter().map(|p| p.x()).collect();
+        let mut y: Vec<f64> = self.grid.points().iter().map(|p| p.y()).collect();
+
+        let nodes: Vec<Cartesian2D> = x.iter().zip(y.iter()).map(|(&x, &y)| Cartesian2D::new(x, y)).collect();
+        let scale = bounding_size(&[&nodes]).max(f64::MIN_POSITIVE);
+
+        // orientation of the starting grid, so the wall normals point into the domain
+        // whichever way round the indices run
+        let orientation = if signed_area(nx, ny, &x, &y) < 0.0 { -1.0 } else { 1.0 };
+
+        self.residuals.clear();
+        let (mut converged, mut collapsed) = (false, false);
+        let mut p = vec![0.0; nx * ny];
+        let mut q = vec![0.0; nx * ny];
+        for _ in 0..self.max_iterations {
+            if !self.controls.is_empty() {
+                self.update_controls(nx, ny, &x, &y, orientation);
+                self.interpolate_controls(nx, ny, &mut p, &mut q);
+            }
+            self.sweep(nx, ny, &mut x, &mut y, &p, &q);
+            let Some(residual) = self.residual(nx, ny, &x, &y, &p, &q) else {
+                collapsed = true;
+                break;
+            };
+            let residual = residual / scale;
+            self.residuals.push(residual);
+            if !residual.is_finite() {
+                break;
+            }
+            if residual < self.tolerance {
+                converged = true;
+                break;
+            }
+        }
+
+        let first = if self.grid.is_periodic() { 0 } else { 1 };
+        let last = if self.grid.is_periodic() { nx - 1 } else { nx - 2 };
+        for j in 1..ny - 1 {
+            for i in first..=last {
+                let k = j * nx + i;
+                self.grid.set_point(i, j, x[k], y[k]);
+            }
+        }
+
+        if collapsed {
+            Err("elliptic grid has nodes whose neighbours collapsed onto a point")
+        } else if converged {
+            Ok(self.residuals.len())
+        } else {
+            Err("elliptic solver did not converge")
+        }
+    }
+
+    // one gauss-seidel sor sweep over the interior. nodes whose neighbours have collapsed
+    // onto them are left where they are, the residual picks them up afterwards
+    fn sweep(&self, nx: usize, ny: usize, x: &mut [f64], y: &mut [f64], p: &[f64], q: &[f64]) -> f64 {
+        let periodic = self.grid.is_periodic();
+        let mut max_correction: f64 = 0.0;
+
+        for j in 1..ny - 1 {
+            for i in if periodic { 0 } else { 1 }..nx - 1 {
+                let k = j * nx + i;
+                let Some((x_star, y_star)) = self.winslow_point(i, j, nx, x, y, p, q) else { continue };
+                let dx = x_star - x[k];
+                let dy = y_star - y[k];
+                x[k] += self.relaxation * dx;
+                y[k] += self.relaxation * dy;
+                max_correction = max_correction.max(dx.hypot(dy));
+            }
+            if periodic {
+                x[j * nx + nx - 1] = x[j * nx];
+                y[j * nx + nx - 1] = y[j * nx];
+            }
+        }
+
+        max_correction
+    }
+
+    // largest winslow residual over the interior of the grid as it stands, each divided by
+    // the 2 (alpha + gamma) of its node, so it is how far the node sits from where the
+    // equations put it. none if any node has collapsed
+    fn residual(&self, nx: usize, ny: usize, x: &[f64], y: &[f64], p: &[f64], q: &[f64]) -> Option<f64> {
+        let periodic = self.grid.is_periodic();
+        let mut max_residual: f64 = 0.0;
+        for j in 1..ny - 1 {
+            for i in if periodic { 0 } else { 1 }..nx - 1 {
+                let k = j * nx + i;
+                let (x_star, y_star) = self.winslow_point(i, j, nx, x, y, p, q)?;
+                max_residual = max_residual.max((x_star - x[k]).hypot(y_star - y[k]));
+            }
+        }
+        Some(max_residual)
+    }
+
+    // where the discrete winslow equations with sources p and q put node (i, j) given its
+    // neighbours, or none if the neighbours have collapsed onto a point
+    #[allow(clippy::too_many_arguments)]
+    fn winslow_point(&self, i: usize, j: usize, nx: usize, x: &[f64], y: &[f64], p: &[f64], q: &[f64]) -> Option<(f64, f64)> {
+        let (west, east) = along_neighbours(i, nx, self.grid.is_periodic());
+        let k = j * nx + i;
+        let (e, w, n, s) = (j * nx + east, j * nx + west, k + nx, k - nx);
+        let (ne, nw, se, sw) = (e + nx, w + nx, e - nx, w - nx);
+
+        let x_xi = 0.5 * (x[e] - x[w]);
+        let y_xi = 0.5 * (y[e] - y[w]);
+        let x_eta = 0.5 * (x[n] - x[s]);
+        let y_eta = 0.5 * (y[n] - y[s]);
+
+        let alpha = x_eta * x_eta + y_eta * y_eta;
+        let beta = x_xi * x_eta + y_xi * y_eta;
+        let gamma = x_xi * x_xi + y_xi * y_xi;
+        let denom = 2.0 * (alpha + gamma);
+        if denom.is_nan() || denom <= 0.0 {
+            return None;
+        }
+
+        let x_cross = 0.25 * (x[ne] - x[nw] - x[se] + x[sw]);
+        let y_cross = 0.25 * (y[ne] - y[nw] - y[se] + y[sw]);
+
+        let jac = x_xi * y_eta - x_eta * y_xi;
+        let source = jac * jac;
+        let x_source = source * (p[k] * x_xi + q[k] * x_eta);
+        let y_source = source * (p[k] * y_xi + q[k] * y_eta);
+
+        let x_star = (alpha * (x[e] + x[w]) + gamma * (x[n] + x[s]) - 2.0 * beta * x_cross + x_source) / denom;
+        let y_star = (alpha * (y[e] + y[w]) + gamma * (y[n] + y[s]) - 2.0 * beta * y_cross + y_source) / denom;
+        Some((x_star, y_star))
+    }
+
+    // recomputes p and q on each controlled boundary from the current interior, following
+    // sorenson: the wanted r_off is orthogonal to the boundary with length equal to the
+    // spacing, r_offoff comes from a one-sided difference into the current grid, and the
+    // poisson equations evaluated at the boundary are then solved for p and q
+    fn update_controls(&mut self, nx: usize, ny: usize, x: &[f64], y: &[f64], orientation: f64) {
+        let omega = self.control_relaxation;
+        let periodic = self.grid.is_periodic();
+
+        for control in self.controls.iter_mut() {
+            let side = control.side;
+            let n_along = control.p.len();
+            let node = |along: usize, off: usize| -> usize {
+                match side {
+                    Side::Bottom => off * nx + along,
+                    Side::Top => (ny - 1 - off) * nx + along,
+                    Side::Left => along * nx + off,
+                    Side::Right => along * nx + nx - 1 - off,
+                }
+            };
+
+            // wanted derivative away from the wall at every node along it
+            let wanted: Vec<(f64, f64)> = (0..n_along)
+                .map(|k| {
+                    let (a, b) = along_neighbours(k, n_along, periodic);
+                    let scale = if (a < k && k < b) || periodic { 0.5 } else { 1.0 };
+                    let t = ((x[node(b, 0)] - x[node(a, 0)]) * scale, (y[node(b, 0)] - y[node(a, 0)]) * scale);
+                    let length = t.0.hypot(t.1).max(f64::MIN_POSITIVE);
+                    let turn = match side {
+                        Side::Bottom | Side::Right => orientation,
+                        Side::Top | Side::Left => -orientation,
+                    };
+                    (-turn * control.spacing * t.1 / length, turn * control.spacing * t.0 / length)
+                })
+                .collect();
+
+            for k in if periodic { 0 } else { 1 }..n_along - 1 {
+                let (before, after) = along_neighbours(k, n_along, periodic);
+                let (b, c, f) = (node(k, 0), node(before, 0), node(after, 0));
+                let (n1, n2) = (node(k, 1), node(k, 2));
+
+                let t = (0.5 * (x[f] - x[c]), 0.5 * (y[f] - y[c]));
+                let tt = (x[f] - 2.0 * x[b] + x[c], y[f] - 2.0 * y[b] + y[c]);
+                let n = wanted[k];
+                let nn = (
+                    0.5 * (-7.0 * x[b] + 8.0 * x[n1] - x[n2]) - 3.0 * n.0,
+                    0.5 * (-7.0 * y[b] + 8.0 * y[n1] - y[n2]) - 3.0 * n.1,
+                );
+                let tn = (0.5 * (wanted[after].0 - wanted[before].0), 0.5 * (wanted[after].1 - wanted[before].1));
+
+                // back to xi / eta derivatives, the off direction runs against the index
+                // direction on the top and right sides
+                let (r_xi, r_eta, r_xixi, r_etaeta, r_xieta) = match side {
+                    Side::Bottom => (t, n, tt, nn, tn),
+                    Side::Top => (t, (-n.0, -n.1), tt, nn, (-tn.0, -tn.1)),
+                    Side::Left => (n, t, nn, tt, tn),
+                    Side::Right => ((-n.0, -n.1), t, nn, tt, (-tn.0, -tn.1)),
+                };
+
+                let alpha = r_eta.0 * r_eta.0 + r_eta.1 * r_eta.1;
+                let beta = r_xi.0 * r_eta.0 + r_xi.1 * r_eta.1;
+                let gamma = r_xi.0 * r_xi.0 + r_xi.1 * r_xi.1;
+                let jac = r_xi.0 * r_eta.1 - r_eta.0 * r_xi.1;
+                if jac.abs() <= f64::MIN_POSITIVE {
+                    continue;
+                }
+
+                let r1 = -(alpha * r_xixi.0 - 2.0 * beta * r_xieta.0 + gamma * r_etaeta.0) / (jac * jac);
+                let r2 = -(alpha * r_xixi.1 - 2.0 * beta * r_xieta.1 + gamma * r_etaeta.1) / (jac * jac);
+                let p_target = (r_eta.1 * r1 - r_eta.0 * r2) / jac;
+                let q_target = (-r_xi.1 * r1 + r_xi.0 * r2) / jac;
+
+                // the first targets come from a poor interior and can be wild, so each
+                // sweep may only move p and q by a fraction of their current size
+                let limit = |current: f64| Self::MAX_CONTROL_GROWTH * current.abs().max(1.0);
+                let dp = omega * (p_target - control.p[k]);
+                let dq = omega * (q_target - control.q[k]);
+                control.p[k] += dp.clamp(-limit(control.p[k]), limit(control.p[k]));
+                control.q[k] += dq.clamp(-limit(control.q[k]), limit(control.q[k]));
+            }
+
+            // boundary end points take their neighbour's value, or the seam value if periodic
+            if !periodic {
+                control.p[0] = control.p[1];
+                control.q[0] = control.q[1];
+            }
+            control.p[n_along - 1] = control.p[if periodic { 0 } else { n_along - 2 }];
+            control.q[n_along - 1] = control.q[if periodic { 0 } else { n_along - 2 }];
+        }
+    }
+
+    const MAX_CONTROL_GROWTH: f64 = 0.1;
+
+    // spreads the boundary values of p and q over the interior with exponential decay
+    fn interpolate_controls(&self, nx: usize, ny: usize, p: &mut [f64], q: &mut [f64]) {
+        p.iter_mut().for_each(|v| *v = 0.0);
+        q.iter_mut().for_each(|v| *v = 0.0);
+
+        for control in self.controls.iter() {
+            for j in 0..ny {
+                for i in 0..nx {
+                    let (along, off) = match control.side {
+                        Side::Bottom => (i, j),
+                        Side::Top => (i, ny - 1 - j),
+                        Side::Left => (j, i),
+                        Side::Right => (j, nx - 1 - i),
+                    };
+                    let weight = (-control.decay * off as f64).exp();
+                    p[j * nx + i] += control.p[along] * weight;
+                    q[j * nx + i] += control.q[along] * weight;
+                }
+            }
+        }
+    }
+}
+
+impl WallControl {
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn spacing(&self) -> f64 {
+        self.spacing
+    }
+
+    // current boundary values of the source terms, one per node along the side
+    pub fn p(&self) -> &[f64] {
+        &self.p
+    }
+
+    pub fn q(&self) -> &[f64] {
+        &self.q
+    }
+}
+
+// (previous, next) index along a line of n points. at the ends of an open line these are
+// clamped, across a periodic seam they skip the duplicated point
+fn along_neighbours(k: usize, n: usize, periodic: bool) -> (usize, usize) {
+    let previous = if k > 0 { k - 1 } else if periodic { n - 2 } else { 0 };
+    let next = if k + 1 < n { k + 1 } else if periodic { 1 } else { k };
+    (previous, next)
+}
+
+fn signed_area(nx: usize, ny: usize, x: &[f64], y: &[f64]) -> f64 {
+    let mut area = 0.0;
+    for j in 0..ny - 1 {
+        for i in 0..nx - 1 {
+            let (a, b, c, d) = (j * nx + i, j * nx + i + 1, (j + 1) * nx + i + 1, (j + 1) * nx + i);
+            area += 0.5 * ((x[c] - x[a]) * (y[d] - y[b]) - (y[c] - y[a]) * (x[d] - x[b]));
+        }
+    }
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::test_utils::{line, min_cell_area};
+    use crate::generator::tfi::TransfiniteInterpolation;
+
+    #[test]
+    fn test_uniform_grid_is_fixed_point() {
+        let grid = TransfiniteInterpolation::new(
+            line((0.0, 0.0), (1.0, 0.0), 6),
+            line((0.0, 1.0), (1.0, 1.0), 6),
+            line((0.0, 0.0), (0.0, 1.0), 6),
+            line((1.0, 0.0), (1.0, 1.0), 6),
+        ).unwrap().generate();
+
+        let mut elliptic = EllipticGenerator::new(grid.clone(), 1e-10).unwrap();
+        assert_eq!(elliptic.solve(), Ok(1));
+        for (a, b) in grid.points().iter().zip(elliptic.grid().points()) {
+            assert!((a.x() - b.x()).abs() < 1e-12 && (a.y() - b.y()).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_kinked_boundary_smoothing() {
+        // bottom boundary with a sharp ramp, tfi carries the kink straight up the grid
+        let n = 21;
+        let bottom: Vec<Cartesian2D> = (0..n)
+            .map(|k| {
+                let x = k as f64 / (n - 1) as f64;
+                Cartesian2D::new(x, if x > 0.5 { 0.4 * (x - 0.5) } else { 0.0 })
+            })
+            .collect();
+        let top = line((0.0, 1.0), (1.0, 1.0), n);
+        let left = line((0.0, 0.0), (0.0, 1.0), 11);
+        let right = line((1.0, 0.2), (1.0, 1.0), 11);
+        let grid = TransfiniteInterpolation::new(bottom, top, left, right).unwrap().generate();
+
+        let mut elliptic = EllipticGenerator::new(grid.clone(), 1e-9).unwrap();
+        elliptic.set_relaxation(1.5).unwrap();
+        assert!(elliptic.solve().is_ok());
+        let smoothed = elliptic.grid();
+
+        // boundaries untouched
+        for i in 0..grid.nx() {
+            for j in [0, grid.ny() - 1] {
+                assert_eq!(grid.point(i, j).x(), smoothed.point(i, j).x());
+                assert_eq!(grid.point(i, j).y(), smoothed.point(i, j).y());
+            }
+        }
+        assert!(min_cell_area(smoothed) > 0.0);
+        // the residual is measured on the grid handed back, so solving it again stops at once
+        assert!(elliptic.residuals().last().unwrap() < &1e-9);
+        let mut again = EllipticGenerator::new(smoothed.clone(), 1e-9).unwrap();
+        assert_eq!(again.solve(), Ok(1));
+    }
+
+    fn bump_channel(nx: usize, ny: usize) -> Grid2D {
+        let bottom: Vec<Cartesian2D> = (0..nx)
+            .map(|k| {
+                let x = k as f64 / (nx - 1) as f64;
+                Cartesian2D::new(x, 0.1 * (std::f64::consts::PI * x).sin().powi(2))
+            })
+            .collect();
+        TransfiniteInterpolation::new(
+            bottom,
+            line((0.0, 1.0), (1.0, 1.0), nx),
+            line((0.0, 0.0), (0.0, 1.0), ny),
+            line((1.0, 0.0), (1.0, 1.0), ny),
+        ).unwrap().generate()
+    }
+
+    // first cell height and the angle (degrees) the grid line leaves the wall at
+    fn wall_cell(grid: &Grid2D, i: usize, j: usize, j_next: usize) -> (f64, f64) {
+        let (b, n) = (grid.point(i, j), grid.point(i, j_next));
+        let (l, r) = (grid.point(i - 1, j), grid.point(i + 1, j));
+        let t = (r.x() - l.x(), r.y() - l.y());
+        let d = (n.x() - b.x(), n.y() - b.y());
+        let height = d.0.hypot(d.1);
+        let cos = (t.0 * d.0 + t.1 * d.1) / (t.0.hypot(t.1) * height);
+        (height, cos.acos().to_degrees())
+    }
+
+    #[test]
+    fn test_wall_control_spacing_and_orthogonality() {
+        let spacing = 0.01;
+        let mut elliptic = EllipticGenerator::new(bump_channel(31, 21), 1e-8).unwrap();
+        elliptic.set_wall_control(Side::Bottom, spacing, 0.3).unwrap();
+        elliptic.set_wall_control(Side::Top, 2.0 * spacing, 0.3).unwrap();
+        elliptic.set_relaxation(1.5).unwrap();
+        assert!(elliptic.solve().is_ok());
+
+        let grid = elliptic.grid();
+        assert!(min_cell_area(grid) > 0.0);
+        for i in 3..grid.nx() - 3 {
+            let (height, angle) = wall_cell(grid, i, 0, 1);
+            assert!((height - spacing).abs() < 0.15 * spacing, "spacing {} at i = {}", height, i);
+            assert!((angle - 90.0).abs() < 3.0, "angle {} at i = {}", angle, i);
+
+            let (height, angle) = wall_cell(grid, i, grid.ny() - 1, grid.ny() - 2);
+            assert!((height - 2.0 * spacing).abs() < 0.15 * 2.0 * spacing, "spacing {} at i = {}", height, i);
+            assert!((angle - 90.0).abs() < 3.0, "angle {} at i = {}", angle, i);
+        }
+    }
+
+    #[test]
+    fn test_laplace_spacing_drifts_without_control() {
+        // same channel without controls keeps roughly uniform spacing at the wall
+        let mut elliptic = EllipticGenerator::new(bump_channel(31, 21), 1e-8).unwrap();
+        elliptic.solve().unwrap();
+        let (height, _) = wall_cell(elliptic.grid(), 15, 0, 1);
+        assert!(height > 0.03);
+    }
+
+    #[test]
+    fn test_rejects_incomplete_grid() {
+        let mut grid = Grid2D::new(3, 3);
+        grid.add_point(0.0, 0.0);
+        assert!(EllipticGenerator::new(grid, 1e-6).is_err());
+
+        let square = TransfiniteInterpolation::new(
+            line((0.0, 0.0), (1.0, 0.0), 3),
+            line((0.0, 1.0), (1.0, 1.0), 3),
+            line((0.0, 0.0), (0.0, 1.0), 3),
+            line((1.0, 0.0), (1.0, 1.0), 3),
+        ).unwrap().generate();
+        assert!(EllipticGenerator::new(square.clone(), f64::NAN).is_err());
+        assert!(EllipticGenerator::new(square, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_collapsed_nodes_are_reported() {
+        // every boundary node on one point leaves the middle node nothing to solve against
+        let mut grid = Grid2D::new(3, 3);
+        for k in 0..9 {
+            let offset = if k == 4 { 0.5 } else { 0.0 };
+            grid.add_point(offset, offset);
+        }
+        let mut elliptic = EllipticGenerator::new(grid, 1e-6).unwrap();
+        assert_eq!(elliptic.solve(), Err("elliptic grid has nodes whose neighbours collapsed onto a point"));
+    }
+}
@@ -0,0 +1,409 @@
+#![allow(dead_code)]
+
+use crate::grid::Grid2D;
+use crate::utils::geometry::Cartesian2D;
+use crate::utils::numerics::{
+    mat2_add, mat2_identity, mat2_inverse, mat2_mul, mat2_scale, mat2_sub,
+    solve_block_tridiagonal, solve_periodic_block_tridiagonal, Mat2, Vec2,
+};
+use super::bounding_size;
+
+// hyperbolic marching stuff //
+// each new layer is found from the previous one by solving the linearised orthogonality
+// and cell area equations (steger and chaussee)
+//     x_xi x_eta + y_xi y_eta = 0
+//     x_xi y_eta - y_xi x_eta = dv
+// implicitly in xi, which is a 2x2 block tridiagonal system per layer (periodic if the
+// body is closed). the body is j = 0 and the grid marches to the left of the direction
+// the points run in, or outwards for a closed body
+pub struct HyperbolicGenerator {
+    body: Vec<Cartesian2D>,
+    closed: bool,
+    n_layers: usize,
+    first_spacing: f64,
+    growth_rate: f64,
+    explicit_dissipation: f64,
+    implicit_dissipation: f64,
+    volume_smoothing: f64,
+}
+
+impl HyperbolicGenerator {
+    // relative tolerance used to decide whether the first and last body points coincide
+    const CLOSED_TOL: f64 = 1e-10;
+
+    pub fn new(body: Vec<Cartesian2D>, n_layers: usize, first_spacing: f64, growth_rate: f64) -> Result<Self, &'static str> {
+        if body.len() < 3 {
+            return Err("body curve must contain at least three points");
+        }
+        if n_layers < 2 {
+            return Err("hyperbolic grid needs at least two layers");
+        }
+        if first_spacing <= 0.0 {
+            return Err("first layer spacing must be positive");
+        }
+        if growth_rate <= 0.0 {
+            return Err("growth rate must be positive");
+        }
+        if body.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("body curve contains repeated consecutive points");
+        }
+
+        let scale = bounding_size(&[&body]);
+        let closed = body[0].distance(&body[body.len() - 1]) <= Self::CLOSED_TOL * scale;
+        if closed && body.len() < 4 {
+            return Err("closed body curve must contain at least three distinct points");
+        }
+
+        Ok(HyperbolicGenerator {
+            body,
+            closed,
+            n_layers,
+            first_spacing,
+            growth_rate,
+            explicit_dissipation: 1.0,
+            implicit_dissipation: 2.0,
+            volume_smoothing: 0.3,
+        })
+    }
+
+    // scale factors on the second difference smoothing added to the right hand side
+    // (explicit) and to the left hand side operator (implicit). the local coefficient also
+    // depends on the step to cell width ratio, distance from the body, grid convergence
+    // and concavity, so these are order one numbers
+    pub fn set_dissipation(&mut self, explicit: f64, implicit: f64) -> Result<(), &'static str> {
+        if explicit < 0.0 || implicit < 0.0 {
+            return Err("dissipation coefficients must not be negative");
+        }
+        self.explicit_dissipation = explicit;
+        self.implicit_dissipation = implicit;
+        Ok(())
+    }
+
+    // weight of the neighbour averaging applied to the cell areas of each layer, in [0, 1).
+    // spreading area out of concave corners is what keeps them from folding
+    pub fn set_volume_smoothing(&mut self, smoothing: f64) -> Result<(), &'static str> {
+        if !(0.0..1.0).contains(&smoothing) {
+            return Err("volume smoothing must be in [0, 1)");
+        }
+        self.volume_smoothing = smoothing;
+        Ok(())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    // marching step between layer k and k + 1
+    pub fn layer_spacing(&self, k: usize) -> f64 {
+        self.first_spacing * self.growth_rate.powi(k as i32)
+    }
+
+    pub fn generate(&self) -> Result<Grid2D, &'static str> {
+        // a closed body is solved on its distinct points, the seam is added back at the end
+        let mut layer: Vec<Vec2> = self.body.iter().map(|p| [p.x(), p.y()]).collect();
+        if self.closed {
+            layer.pop();
+        }
+        let n = layer.len();
+        let nx = if self.closed { n + 1 } else { n };
+
+        // for a closed body march away from the enclosed area, otherwise to the left
+        let side = if self.closed && polygon_area(&layer) > 0.0 { -1.0 } else { 1.0 };
+
+        let mut layers: Vec<Vec<Vec2>> = Vec::with_capacity(self.n_layers);
+        layers.push(layer);
+        for k in 1..self.n_layers {
+            let previous = if k > 1 { Some(layers[k - 2].as_slice()) } else { None };
+            let next = self.march(&layers[k - 1], previous, k, side)?;
+            layers.push(next);
+        }
+
+        let mut grid = Grid2D::new(nx, self.n_layers);
+        for layer in layers.iter() {
+            for point in layer.iter() {
+                grid.add_point(point[0], point[1]);
+            }
+            if self.closed {
+                grid.add_point(layer[0][0], layer[0][1]);
+            }
+        }
+        grid.set_periodic(self.closed)?;
+        Ok(grid)
+    }
+
+    // finds layer k from layer k - 1 (and k - 2 for the convergence sensor)
+    fn march(&self, layer: &[Vec2], previous: Option<&[Vec2]>, k: usize, side: f64) -> Result<Vec<Vec2>, &'static str> {
+        let n = layer.len();
+        let step = self.layer_spacing(k - 1);
+        let neighbours = |i: usize| -> (usize, usize) {
+            if self.closed {
+                ((i + n - 1) % n, (i + 1) % n)
+            } else {
+                (i.saturating_sub(1), (i + 1).min(n - 1))
+            }
+        };
+
+        // tangents and the cell areas wanted for the new layer
+        let tangents: Vec<Vec2> = (0..n)
+            .map(|i| {
+                let (a, b) = neighbours(i);
+                let scale = if self.closed || (i > 0 && i < n - 1) { 0.5 } else { 1.0 };
+                [scale * (layer[b][0] - layer[a][0]), scale * (layer[b][1] - layer[a][1])]
+            })
+            .collect();
+        // the cell area is measured against the new layer's arc length, estimated by
+        // pushing every point out along its normal, so curved bodies still march by step
+        let predicted: Vec<Vec2> = (0..n)
+            .map(|i| {
+                let t = tangents[i];
+                let length = t[0].hypot(t[1]).max(f64::MIN_POSITIVE);
+                [layer[i][0] - side * step * t[1] / length, layer[i][1] + side * step * t[0] / length]
+            })
+            .collect();
+        let mut volumes: Vec<f64> = (0..n)
+            .map(|i| {
+                let (a, b) = neighbours(i);
+                let scale = if self.closed || (i > 0 && i < n - 1) { 0.5 } else { 1.0 };
+                let t = [scale * (predicted[b][0] - predicted[a][0]), scale * (predicted[b][1] - predicted[a][1])];
+                step * t[0].hypot(t[1])
+            })
+            .collect();
+        for _ in 0..Self::VOLUME_SMOOTHING_PASSES {
+            volumes = (0..n)
+                .map(|i| {
+                    let (a, b) = neighbours(i);
+                    (1.0 - self.volume_smoothing) * volumes[i] + 0.5 * self.volume_smoothing * (volumes[a] + volumes[b])
+                })
+                .collect();
+        }
+
+        let mut lower: Vec<Mat2> = Vec::with_capacity(n);
+        let mut diag: Vec<Mat2> = Vec::with_capacity(n);
+        let mut upper: Vec<Mat2> = Vec::with_capacity(n);
+        let mut rhs: Vec<Vec2> = Vec::with_capacity(n);
+        let zero: Mat2 = [[0.0, 0.0], [0.0, 0.0]];
+        let layer_scale = (k as f64 / (self.n_layers - 1) as f64).sqrt();
+
+        // zeroth order guess for r_eta, which satisfies both equations exactly
+        let mut r_etas: Vec<Vec2> = Vec::with_capacity(n);
+        for i in 0..n {
+            let t = tangents[i];
+            let t2 = t[0] * t[0] + t[1] * t[1];
+            if t2 <= f64::MIN_POSITIVE {
+                return Err("body curve has a zero length tangent");
+            }
+            let dv = side * volumes[i];
+            r_etas.push([-dv * t[1] / t2, dv * t[0] / t2]);
+        }
+
+        for i in 0..n {
+            let (a, b) = neighbours(i);
+            let t = tangents[i];
+            let r_eta = r_etas[i];
+
+            if !self.closed && (i == 0 || i == n - 1) {
+                // open ends follow their neighbour: dr[0] = dr[1], dr[n-1] = dr[n-2]
+                lower.push(if i == 0 { zero } else { mat2_scale(&mat2_identity(), -1.0) });
+                diag.push(mat2_identity());
+                upper.push(if i == 0 { mat2_scale(&mat2_identity(), -1.0) } else { zero });
+                rhs.push([0.0, 0.0]);
+                continue;
+            }
+
+            // linearised system a r_xi + b r_eta = f, premultiplied by b^-1
+            let a_mat: Mat2 = [[r_eta[0], r_eta[1]], [r_eta[1], -r_eta[0]]];
+            let b_mat: Mat2 = [[t[0], t[1]], [-t[1], t[0]]];
+            let c = mat2_mul(&mat2_inverse(&b_mat)?, &a_mat);
+
+            // dissipation coefficient after chan: grows with the ratio of marching step to
+            // cell width, with distance from the body, where successive layers are shrinking
+            // (grid convergence) and at concave corners
+            let spectral = r_eta[0].hypot(r_eta[1]) / t[0].hypot(t[1]);
+            let width = |points: &[Vec2]| -> f64 {
+                distance(&points[b], &points[i]) + distance(&points[i], &points[a])
+            };
+            let convergence = match previous {
+                Some(previous) => (width(previous) / width(layer).max(f64::MIN_POSITIVE))
+                    .powf(2.0 / layer_scale)
+                    .clamp(0.1, Self::MAX_SENSOR),
+                None => 1.0,
+            };
+            let concavity = concavity_factor(&layer[a], &layer[i], &layer[b], side);
+            let scale = spectral * layer_scale * convergence * concavity;
+            let eps_e = self.explicit_dissipation * scale;
+            let eps_i = self.implicit_dissipation * scale;
+
+            // on convex parts the second difference of the layer points back towards the
+            // body, so only its tangential part is kept there, otherwise convex layers
+            // shrink in towards their centre of curvature
+            let mut second = [layer[b][0] - 2.0 * layer[i][0] + layer[a][0], layer[b][1] - 2.0 * layer[i][1] + layer[a][1]];
+            let normal_length = r_eta[0].hypot(r_eta[1]).max(f64::MIN_POSITIVE);
+            let normal = [r_eta[0] / normal_length, r_eta[1] / normal_length];
+            let backwards = second[0] * normal[0] + second[1] * normal[1];
+            if backwards < 0.0 {
+                second = [second[0] - backwards * normal[0], second[1] - backwards * normal[1]];
+            }
+            let smoothing = [eps_e * second[0], eps_e * second[1]];
+
+            // the implicit smoothing is limited the same way, to the tangential direction
+            // on convex parts of the layer
+            let damping = if backwards < 0.0 {
+                let length = t[0].hypot(t[1]);
+                let unit = [t[0] / length, t[1] / length];
+                mat2_scale(&[[unit[0] * unit[0], unit[0] * unit[1]], [unit[1] * unit[0], unit[1] * unit[1]]], eps_i)
+            } else {
+                mat2_scale(&mat2_identity(), eps_i)
+            };
+
+            let half_c = mat2_scale(&c, 0.5);
+            lower.push(mat2_sub(&mat2_scale(&damping, -1.0), &half_c));
+            diag.push(mat2_add(&mat2_identity(), &mat2_scale(&damping, 2.0)));
+            upper.push(mat2_add(&mat2_scale(&damping, -1.0), &half_c));
+            rhs.push([r_eta[0] + smoothing[0], r_eta[1] + smoothing[1]]);
+        }
+
+        let delta = if self.closed {
+            solve_periodic_block_tridiagonal(&lower, &diag, &upper, &rhs)?
+        } else {
+            solve_block_tridiagonal(&lower, &diag, &upper, &rhs)?
+        };
+
+        let next: Vec<Vec2> = layer
+            .iter()
+            .zip(delta.iter())
+            .map(|(p, d)| [p[0] + d[0], p[1] + d[1]])
+            .collect();
+
+        // every cell of the new layer must keep the orientation of the march
+        let cells = if self.closed { n } else { n - 1 };
+        for i in 0..cells {
+            let b = (i + 1) % n;
+            let area = quad_area(&layer[i], &layer[b], &next[b], &next[i]);
+            if area * side <= 0.0 {
+                return Err("hyperbolic march folded a cell, increase dissipation or volume smoothing");
+            }
+        }
+        Ok(next)
+    }
+
+    const VOLUME_SMOOTHING_PASSES: usize = 4;
+    const MAX_SENSOR: f64 = 10.0;
+}
+
+fn distance(a: &Vec2, b: &Vec2) -> f64 {
+    (b[0] - a[0]).hypot(b[1] - a[1])
+}
+
+// 1 / sin^2(theta / 2) for the angle theta the curve makes on the marching side at b when
+// that angle is less than pi, 1 on convex or straight parts of the curve
+fn concavity_factor(a: &Vec2, b: &Vec2, c: &Vec2, side: f64) -> f64 {
+    let u = [b[0] - a[0], b[1] - a[1]];
+    let v = [c[0] - b[0], c[1] - b[1]];
+    let turn = (u[0] * v[1] - u[1] * v[0]).atan2(u[0] * v[0] + u[1] * v[1]);
+    let theta = std::f64::consts::PI - side * turn;
+    if theta < std::f64::consts::PI {
+        let half = (0.5 * theta).sin();
+        (1.0 / (half * half).max(f64::MIN_POSITIVE)).min(HyperbolicGenerator::MAX_SENSOR)
+    } else {
+        1.0
+    }
+}
+
+fn polygon_area(points: &[Vec2]) -> f64 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            0.5 * (a[0] * b[1] - b[0] * a[1])
+        })
+        .sum()
+}
+
+fn quad_area(a: &Vec2, b: &Vec2, c: &Vec2, d: &Vec2) -> f64 {
+    0.5 * ((c[0] - a[0]) * (d[1] - b[1]) - (c[1] - a[1]) * (d[0] - b[0]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(radius: f64, n: usize, clockwise: bool) -> Vec<Cartesian2D> {
+        (0..=n)
+            .map(|k| {
+                let sign = if clockwise { -1.0 } else { 1.0 };
+                let theta = sign * 2.0 * std::f64::consts::PI * (k % n) as f64 / n as f64;
+                Cartesian2D::new(radius * theta.cos(), radius * theta.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_circle_gives_concentric_layers() {
+        for clockwise in [false, true] {
+            let hyperbolic = HyperbolicGenerator::new(circle(1.0, 64, clockwise), 20, 0.01, 1.2).unwrap();
+            assert!(hyperbolic.is_closed());
+            let grid = hyperbolic.generate().unwrap();
+            assert_eq!((grid.nx(), grid.ny()), (65, 20));
+
+            let mut radius = 1.0;
+            for j in 0..grid.ny() {
+                let radii: Vec<f64> = (0..grid.nx()).map(|i| grid.point(i, j).x().hypot(grid.point(i, j).y())).collect();
+                let (min, max) = radii.iter().fold((f64::INFINITY, 0.0_f64), |(lo, hi), &r| (lo.min(r), hi.max(r)));
+                assert!(max - min < 1e-9 * max, "layer {} is not circular", j);
+                // marching distance tracks the requested spacing to within the chord error
+                assert!((min - radius).abs() < 0.01 * radius, "layer {} radius {} wanted {}", j, min, radius);
+                radius += hyperbolic.layer_spacing(j);
+
+                let (first, last) = (grid.point(0, j), grid.point(grid.nx() - 1, j));
+                assert_eq!((first.x(), first.y()), (last.x(), last.y()));
+            }
+            assert!(grid.is_periodic());
+        }
+    }
+
+    #[test]
+    fn test_flat_plate_marches_left() {
+        let body: Vec<Cartesian2D> = (0..21).map(|k| Cartesian2D::new(k as f64 / 20.0, 0.0)).collect();
+        let hyperbolic = HyperbolicGenerator::new(body, 10, 0.01, 1.1).unwrap();
+        assert!(!hyperbolic.is_closed());
+        let grid = hyperbolic.generate().unwrap();
+        assert!(!grid.is_periodic());
+
+        let mut height = 0.0;
+        for j in 0..grid.ny() {
+            for i in 0..grid.nx() {
+                let p = grid.point(i, j);
+                assert!((p.x() - i as f64 / 20.0).abs() < 1e-9);
+                assert!((p.y() - height).abs() < 1e-9);
+            }
+            height += hyperbolic.layer_spacing(j);
+        }
+    }
+
+    #[test]
+    fn test_concave_corner_does_not_fold() {
+        // an l-shaped wall marched into the inside of the corner
+        let mut body: Vec<Cartesian2D> = (0..=20).map(|k| Cartesian2D::new(1.0 - k as f64 / 20.0, 0.0)).collect();
+        body.extend((1..=20).map(|k| Cartesian2D::new(0.0, k as f64 / 20.0)));
+        body.reverse();
+        let mut hyperbolic = HyperbolicGenerator::new(body, 15, 0.01, 1.15).unwrap();
+        hyperbolic.set_dissipation(2.0, 4.0).unwrap();
+        let grid = hyperbolic.generate().unwrap();
+
+        for j in 0..grid.ny() - 1 {
+            for i in 0..grid.nx() - 1 {
+                let (a, b) = (grid.point(i, j), grid.point(i + 1, j));
+                let (c, d) = (grid.point(i + 1, j + 1), grid.point(i, j + 1));
+                let area = quad_area(&[a.x(), a.y()], &[b.x(), b.y()], &[c.x(), c.y()], &[d.x(), d.y()]);
+                assert!(area > 0.0, "cell ({}, {}) folded", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        let body = vec![Cartesian2D::new(0.0, 0.0), Cartesian2D::new(1.0, 0.0)];
+        assert!(HyperbolicGenerator::new(body, 10, 0.01, 1.1).is_err());
+        assert!(HyperbolicGenerator::new(circle(1.0, 16, false), 10, -0.01, 1.1).is_err());
+    }
+}
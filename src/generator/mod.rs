@@ -0,0 +1,40 @@
+#![allow(dead_code)]
+
+use crate::utils::geometry::Cartesian2D;
+
+pub mod tfi;
+pub mod elliptic;
+pub mod hyperbolic;
+pub mod topology;
+
+#[cfg(test)]
+mod test_utils;
+
+pub(crate) fn normalised_arc_length(points: &[Cartesian2D]) -> Vec<f64> {
+    let mut s = Vec::with_capacity(points.len());
+    let mut total: f64 = 0.0;
+    s.push(0.0);
+    for pair in points.windows(2) {
+        total += pair[0].distance(&pair[1]);
+        s.push(total);
+    }
+
+    if total > 0.0 {
+        s.iter_mut().for_each(|si| *si /= total);
+    } else {
+        // degenerate (collapsed) boundary, fall back to index spacing
+        let n = (points.len() - 1).max(1) as f64;
+        s.iter_mut().enumerate().for_each(|(k, si)| *si = k as f64 / n);
+    }
+    s
+}
+
+pub(crate) fn bounding_size(boundaries: &[&[Cartesian2D]]) -> f64 {
+    let mut min = (f64::INFINITY, f64::INFINITY);
+    let mut max = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for point in boundaries.iter().flat_map(|b| b.iter()) {
+        min = (min.0.min(point.x()), min.1.min(point.y()));
+        max = (max.0.max(point.x()), max.1.max(point.y()));
+    }
+    (max.0 - min.0).hypot(max.1 - min.1)
+}
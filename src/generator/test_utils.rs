@@ -0,0 +1,28 @@
+use crate::grid::Grid2D;
+use crate::utils::geometry::Cartesian2D;
+
+// shared by the generator tests //
+
+// n evenly spaced points from p0 to p1
+pub(crate) fn line(p0: (f64, f64), p1: (f64, f64), n: usize) -> Vec<Cartesian2D> {
+    (0..n)
+        .map(|k| {
+            let t = k as f64 / (n - 1) as f64;
+            Cartesian2D::new(p0.0 + t * (p1.0 - p0.0), p0.1 + t * (p1.1 - p0.1))
+        })
+        .collect()
+}
+
+// smallest signed cell area, negative if any cell is folded over
+pub(crate) fn min_cell_area(grid: &Grid2D) -> f64 {
+    let mut min_area = f64::INFINITY;
+    for j in 0..grid.ny() - 1 {
+        for i in 0..grid.nx() - 1 {
+            let (a, b) = (grid.point(i, j), grid.point(i + 1, j));
+            let (c, d) = (grid.point(i + 1, j + 1), grid.point(i, j + 1));
+            let area = 0.5 * ((c.x() - a.x()) * (d.y() - b.y()) - (c.y() - a.y()) * (d.x() - b.x()));
+            min_area = min_area.min(area);
+        }
+    }
+    min_area
+}
@@ -0,0 +1,183 @@
+#![allow(dead_code)]
+
+use crate::grid::Grid2D;
+use crate::utils::geometry::Cartesian2D;
+use super::{bounding_size, normalised_arc_length};
+
+// transfinite interpolation stuff //
+// the four boundaries are discrete point lists. bottom and top run in the i direction
+// (left to right), left and right run in the j direction (bottom to top), so the corners
+// are bottom[0] = left[0], bottom[nx-1] = right[0], top[0] = left[ny-1], top[nx-1] = right[ny-1]
+pub struct TransfiniteInterpolation {
+    bottom: Vec<Cartesian2D>,
+    top: Vec<Cartesian2D>,
+    left: Vec<Cartesian2D>,
+    right: Vec<Cartesian2D>,
+}
+
+impl TransfiniteInterpolation {
+    // relative tolerance on corner mismatch, scaled by the size of the boundary
+    const CORNER_TOL: f64 = 1e-8;
+
+    pub fn new(
+        bottom: Vec<Cartesian2D>,
+        top: Vec<Cartesian2D>,
+        left: Vec<Cartesian2D>,
+        right: Vec<Cartesian2D>,
+    ) -> Result<Self, &'static str> {
+        if bottom.len() < 2 || left.len() < 2 {
+            return Err("boundaries must contain at least two points");
+        }
+        if bottom.len() != top.len() {
+            return Err("bottom and top boundaries must have the same number of points");
+        }
+        if left.len() != right.len() {
+            return Err("left and right boundaries must have the same number of points");
+        }
+
+        let nx = bottom.len();
+        let ny = left.len();
+        let scale = bounding_size(&[&bottom, &top, &left, &right]).max(f64::MIN_POSITIVE);
+        let tol = Self::CORNER_TOL * scale;
+
+        let corners = [
+            (bottom[0], left[0]),
+            (bottom[nx - 1], right[0]),
+            (top[0], left[ny - 1]),
+            (top[nx - 1], right[ny - 1]),
+        ];
+        if corners.iter().any(|(a, b)| a.distance(b) > tol) {
+            return Err("boundary corners do not meet");
+        }
+
+        Ok(TransfiniteInterpolation { bottom, top, left, right })
+    }
+
+    pub fn nx(&self) -> usize {
+        self.bottom.len()
+    }
+
+    pub fn ny(&self) -> usize {
+        self.left.len()
+    }
+
+    pub fn generate(&self) -> Grid2D {
+        let nx = self.nx();
+        let ny = self.ny();
+        let mut grid = Grid2D::new(nx, ny);
+
+        // normalised arc length along each boundary, used as the blending parameters so
+        // that boundary clustering is carried into the interior
+        let s_bottom = normalised_arc_length(&self.bottom);
+        let s_top = normalised_arc_length(&self.top);
+        let s_left = normalised_arc_length(&self.left);
+        let s_right = normalised_arc_length(&self.right);
+
+        let p00 = self.bottom[0];
+        let p10 = self.bottom[nx - 1];
+        let p01 = self.top[0];
+        let p11 = self.top[nx - 1];
+
+        for j in 0..ny {
+            for i in 0..nx {
+                // intersect the line joining bottom/top parameters with the one joining
+                // left/right parameters to get (u, v) for this node
+                let d_bt = s_top[i] - s_bottom[i];
+                let d_lr = s_right[j] - s_left[j];
+                let denom = 1.0 - d_bt * d_lr;
+                let u = (s_bottom[i] + s_left[j] * d_bt) / denom;
+                let v = (s_left[j] + s_bottom[i] * d_lr) / denom;
+
+                let (b, t, l, r) = (self.bottom[i], self.top[i], self.left[j], self.right[j]);
+                let x = (1.0 - u) * l.x() + u * r.x() + (1.0 - v) * b.x() + v * t.x()
+                    - ((1.0 - u) * (1.0 - v) * p00.x() + u * (1.0 - v) * p10.x()
+                        + (1.0 - u) * v * p01.x() + u * v * p11.x());
+                let y = (1.0 - u) * l.y() + u * r.y() + (1.0 - v) * b.y() + v * t.y()
+                    - ((1.0 - u) * (1.0 - v) * p00.y() + u * (1.0 - v) * p10.y()
+                        + (1.0 - u) * v * p01.y() + u * v * p11.y());
+
+                // keep the boundary nodes exactly as given
+                let (x, y) = if j == 0 {
+                    (b.x(), b.y())
+                } else if j == ny - 1 {
+                    (t.x(), t.y())
+                } else if i == 0 {
+                    (l.x(), l.y())
+                } else if i == nx - 1 {
+                    (r.x(), r.y())
+                } else {
+                    (x, y)
+                };
+                grid.add_point(x, y);
+            }
+        }
+
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::test_utils::line;
+
+    const TOL: f64 = 1e-12;
+
+    #[test]
+    fn test_tfi_unit_square() {
+        let tfi = TransfiniteInterpolation::new(
+            line((0.0, 0.0), (1.0, 0.0), 5),
+            line((0.0, 1.0), (1.0, 1.0), 5),
+            line((0.0, 0.0), (0.0, 1.0), 3),
+            line((1.0, 0.0), (1.0, 1.0), 3),
+        ).unwrap();
+        let grid = tfi.generate();
+
+        assert!(grid.is_complete());
+        assert_eq!((grid.nx(), grid.ny()), (5, 3));
+        for j in 0..3 {
+            for i in 0..5 {
+                let p = grid.point(i, j);
+                assert!((p.x() - i as f64 / 4.0).abs() < TOL);
+                assert!((p.y() - j as f64 / 2.0).abs() < TOL);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tfi_curved_top() {
+        // bottom flat, top a circular arc, interior should stay between the two
+        let n = 11;
+        let bottom = line((-1.0, 0.0), (1.0, 0.0), n);
+        let top: Vec<Cartesian2D> = (0..n)
+            .map(|k| {
+                let theta = std::f64::consts::PI * (1.0 - k as f64 / (n - 1) as f64);
+                Cartesian2D::new(2.0 * theta.cos(), 2.0 * theta.sin())
+            })
+            .collect();
+        let left = line((-1.0, 0.0), (-2.0, 0.0), 4);
+        let right = line((1.0, 0.0), (2.0, 0.0), 4);
+        let grid = TransfiniteInterpolation::new(bottom, top.clone(), left, right)
+            .unwrap()
+            .generate();
+
+        for (i, expected) in top.iter().enumerate() {
+            let p = grid.point(i, 3);
+            assert!((p.x() - expected.x()).abs() < TOL && (p.y() - expected.y()).abs() < TOL);
+        }
+        for p in grid.points() {
+            assert!(p.y() >= -TOL);
+        }
+    }
+
+    #[test]
+    fn test_tfi_rejects_open_corner() {
+        let result = TransfiniteInterpolation::new(
+            line((0.0, 0.0), (1.0, 0.0), 5),
+            line((0.0, 1.0), (1.0, 1.0), 5),
+            line((0.0, 0.0), (0.0, 1.0), 3),
+            line((1.1, 0.0), (1.0, 1.0), 3),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,392 @@
+#![allow(dead_code)]
+
+use crate::grid::{Grid2D, Side, WakeCut};
+use crate::utils::geometry::Cartesian2D;
+use crate::utils::stretching::Stretching;
+use super::bounding_size;
+use super::tfi::TransfiniteInterpolation;
+
+// c-grid stuff //
+// the j = 0 line runs from the outflow along the lower side of the wake cut to the trailing
+// edge, round the body (lower surface, leading edge, upper surface) and back out along the
+// upper side of the wake cut. the far field is a semicircle about the trailing edge joined to
+// straight lines running back to the outflow, and the interior is filled by tfi
+pub struct CGridGenerator {
+    body: Vec<Cartesian2D>,
+    n_wake: usize,
+    n_normal: usize,
+    wake_length: f64,
+    farfield_radius: f64,
+    normal_stretching: Stretching,
+}
+
+impl CGridGenerator {
+    // relative tolerance for the trailing edge being closed
+    const TE_TOL: f64 = 1e-10;
+
+    // body is a closed curve with a sharp trailing edge (first point = last point), in
+    // either direction. n_wake counts the wake points on one side including the trailing edge
+    pub fn new(body: Vec<Cartesian2D>, n_wake: usize, n_normal: usize) -> Result<Self, &'static str> {
+        if body.len() < 5 {
+            return Err("body curve must contain at least five points");
+        }
+        if n_wake < 2 {
+            return Err("c-grid needs at least two wake points");
+        }
+        if n_normal < 2 {
+            return Err("c-grid needs at least two points normal to the body");
+        }
+        let scale = bounding_size(&[&body]);
+        if body[0].distance(&body[body.len() - 1]) > Self::TE_TOL * scale {
+            return Err("c-grid needs a sharp trailing edge, the body must start and end at the same point");
+        }
+        if body.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("body curve contains repeated consecutive points");
+        }
+
+        Ok(CGridGenerator {
+            body,
+            n_wake,
+            n_normal,
+            wake_length: 5.0,
+            farfield_radius: 10.0,
+            normal_stretching: Stretching::Uniform,
+        })
+    }
+
+    // distance from the trailing edge to the outflow, in chords
+    pub fn set_wake_length(&mut self, wake_length: f64) -> Result<(), &'static str> {
+        if wake_length <= 0.0 {
+            return Err("wake length must be positive");
+        }
+        self.wake_length = wake_length;
+        Ok(())
+    }
+
+    // radius of the far field semicircle about the trailing edge, in chords
+    pub fn set_farfield_radius(&mut self, radius: f64) -> Result<(), &'static str> {
+        if radius <= 1.0 {
+            return Err("far field radius must be larger than the chord");
+        }
+        self.farfield_radius = radius;
+        Ok(())
+    }
+
+    // distribution of points from the body (and wake cut) out to the far field
+    pub fn set_normal_stretching(&mut self, stretching: Stretching) {
+        self.normal_stretching = stretching;
+    }
+
+    pub fn generate(&self) -> Result<Grid2D, &'static str> {
+        // clockwise body, so that marching away from j = 0 is to the left of increasing i
+        let mut body = self.body.clone();
+        if signed_area(&body) > 0.0 {
+            body.reverse();
+        }
+        // the trailing edge is only closed to within tolerance, make the cut exact
+        let te = body[0];
+        let last = body.len() - 1;
+        body[last] = te;
+        let le = *body
+            .iter()
+            .max_by(|a, b| te.distance(a).total_cmp(&te.distance(b)))
+            .ok_or("body curve is empty")?;
+        let chord = te.distance(&le);
+
+        // chordwise and normal unit vectors, the wake leaves along the chord line
+        let e = ((te.x() - le.x()) / chord, (te.y() - le.y()) / chord);
+        let f = (-e.1, e.0);
+        let local = |u: f64, v: f64| Cartesian2D::new(te.x() + u * e.0 + v * f.0, te.y() + u * e.1 + v * f.1);
+
+        // wake spacing grows away from the trailing edge, starting from the body's panel size
+        let wake_length = self.wake_length * chord;
+        let radius = self.farfield_radius * chord;
+        let te_spacing = 0.5 * (body[0].distance(&body[1]) + body[body.len() - 2].distance(&body[body.len() - 1]));
+        let wake_stretching = match Stretching::geometric_from_spacing(te_spacing / wake_length, self.n_wake) {
+            Ok(stretching) => stretching,
+            Err(_) => Stretching::Uniform,
+        };
+        let wake = wake_stretching.distribute(self.n_wake)?;
+
+        // inner boundary: lower wake (outflow to te), body, upper wake (te to outflow)
+        let mut inner: Vec<Cartesian2D> = wake.iter().rev().map(|&s| local(s * wake_length, 0.0)).collect();
+        inner.pop();
+        inner.extend(body.iter().copied());
+        inner.extend(wake.iter().skip(1).map(|&s| local(s * wake_length, 0.0)));
+
+        // outer boundary matched segment by segment to the inner one
+        let mut outer: Vec<Cartesian2D> = wake.iter().rev().map(|&s| local(s * wake_length, -radius)).collect();
+        outer.pop();
+        let arc = super::normalised_arc_length(&body);
+        outer.extend(arc.iter().map(|&t| {
+            let phi = -0.5 * std::f64::consts::PI - std::f64::consts::PI * t;
+            local(radius * phi.cos(), radius * phi.sin())
+        }));
+        outer.extend(wake.iter().skip(1).map(|&s| local(s * wake_length, radius)));
+
+        let normal = self.normal_stretching.distribute(self.n_normal)?;
+        let left: Vec<Cartesian2D> = normal.iter().map(|&s| local(wake_length, -s * radius)).collect();
+        let right: Vec<Cartesian2D> = normal.iter().map(|&s| local(wake_length, s * radius)).collect();
+
+        let nx = inner.len();
+        let mut grid = TransfiniteInterpolation::new(inner, outer, left, right)?.generate();
+        grid.add_wake_cut(WakeCut::new(0..=self.n_wake - 1, nx - self.n_wake..=nx - 1)?)?;
+        grid.add_patch("wall", Side::Bottom, self.n_wake - 1..=nx - self.n_wake)?;
+        grid.add_patch("farfield", Side::Top, 0..=nx - 1)?;
+        grid.add_patch("outflow", Side::Left, 0..=self.n_normal - 1)?;
+        grid.add_patch("outflow", Side::Right, 0..=self.n_normal - 1)?;
+        Ok(grid)
+    }
+}
+
+// o-grid stuff //
+// the j = 0 line runs once round the closed body, clockwise, starting and finishing at its
+// first point. the far field is a circle about the middle of the body and the i = 0 and
+// i = nx - 1 columns are the same line, recorded on the grid as a periodic seam
+pub struct OGridGenerator {
+    body: Vec<Cartesian2D>,
+    n_normal: usize,
+    farfield_radius: f64,
+    normal_stretching: Stretching,
+}
+
+impl OGridGenerator {
+    // relative tolerance for the body being closed
+    const CLOSED_TOL: f64 = 1e-10;
+
+    // body is a closed curve (first point = last point) in either direction
+    pub fn new(body: Vec<Cartesian2D>, n_normal: usize) -> Result<Self, &'static str> {
+        if body.len() < 4 {
+            return Err("body curve must contain at least three distinct points");
+        }
+        if n_normal < 2 {
+            return Err("o-grid needs at least two points normal to the body");
+        }
+        let scale = bounding_size(&[&body]);
+        if body[0].distance(&body[body.len() - 1]) > Self::CLOSED_TOL * scale {
+            return Err("o-grid needs a closed body, it must start and end at the same point");
+        }
+        if body.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("body curve contains repeated consecutive points");
+        }
+
+        Ok(OGridGenerator {
+            body,
+            n_normal,
+            farfield_radius: 10.0,
+            normal_stretching: Stretching::Uniform,
+        })
+    }
+
+    // radius of the far field circle, in body lengths (the largest distance from the
+    // first body point, so the chord of an aerofoil or the diameter of a cylinder)
+    pub fn set_farfield_radius(&mut self, radius: f64) -> Result<(), &'static str> {
+        if radius <= 1.0 {
+            return Err("far field radius must be larger than the body");
+        }
+        self.farfield_radius = radius;
+        Ok(())
+    }
+
+    // distribution of points from the body out to the far field
+    pub fn set_normal_stretching(&mut self, stretching: Stretching) {
+        self.normal_stretching = stretching;
+    }
+
+    pub fn generate(&self) -> Result<Grid2D, &'static str> {
+        let mut body = self.body.clone();
+        if signed_area(&body) > 0.0 {
+            body.reverse();
+        }
+        let start = body[0];
+        let last = body.len() - 1;
+        body[last] = start;
+
+        let length = body.iter().map(|p| start.distance(p)).fold(0.0, f64::max);
+        let radius = self.farfield_radius * length;
+        let n_distinct = last as f64;
+        let centre = Cartesian2D::new(
+            body[..last].iter().map(|p| p.x()).sum::<f64>() / n_distinct,
+            body[..last].iter().map(|p| p.y()).sum::<f64>() / n_distinct,
+        );
+
+        // far field points matched to the body by arc length, clockwise from the seam
+        let theta0 = (start.y() - centre.y()).atan2(start.x() - centre.x());
+        let arc = super::normalised_arc_length(&body);
+        let mut outer: Vec<Cartesian2D> = arc
+            .iter()
+            .map(|&t| {
+                let theta = theta0 - 2.0 * std::f64::consts::PI * t;
+                Cartesian2D::new(centre.x() + radius * theta.cos(), centre.y() + radius * theta.sin())
+            })
+            .collect();
+        outer[last] = outer[0];
+
+        let normal = self.normal_stretching.distribute(self.n_normal)?;
+        let seam: Vec<Cartesian2D> = normal
+            .iter()
+            .map(|&s| Cartesian2D::new(start.x() + s * (outer[0].x() - start.x()), start.y() + s * (outer[0].y() - start.y())))
+            .collect();
+
+        let mut grid = TransfiniteInterpolation::new(body, outer, seam.clone(), seam)?.generate();
+        grid.set_periodic(true)?;
+        grid.add_patch("wall", Side::Bottom, 0..=last)?;
+        grid.add_patch("farfield", Side::Top, 0..=last)?;
+        Ok(grid)
+    }
+}
+
+fn signed_area(points: &[Cartesian2D]) -> f64 {
+    points
+        .windows(2)
+        .map(|pair| 0.5 * (pair[0].x() * pair[1].y() - pair[1].x() * pair[0].y()))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::test_utils::min_cell_area;
+
+    // naca 0012 with a closed trailing edge, selig order (te, upper, le, lower, te)
+    fn naca0012(n: usize) -> Vec<Cartesian2D> {
+        let thickness = |x: f64| {
+            0.6 * (0.2969 * x.sqrt() - 0.1260 * x - 0.3516 * x * x + 0.2843 * x.powi(3) - 0.1036 * x.powi(4))
+        };
+        let xs: Vec<f64> = (0..n)
+            .map(|k| 0.5 * (1.0 - (std::f64::consts::PI * k as f64 / (n - 1) as f64).cos()))
+            .collect();
+        let mut points: Vec<Cartesian2D> = xs.iter().rev().map(|&x| Cartesian2D::new(x, thickness(x))).collect();
+        points.extend(xs.iter().skip(1).map(|&x| Cartesian2D::new(x, -thickness(x))));
+        points
+    }
+
+    #[test]
+    fn test_c_grid_naca0012() {
+        let body = naca0012(65);
+        let mut generator = CGridGenerator::new(body.clone(), 25, 33).unwrap();
+        generator.set_normal_stretching(Stretching::geometric_from_spacing(1e-4, 33).unwrap());
+        let grid = generator.generate().unwrap();
+
+        assert_eq!(grid.nx(), 2 * 24 + body.len());
+        assert_eq!(grid.ny(), 33);
+        assert!(min_cell_area(&grid) > 0.0);
+
+        // wake cut points coincide and the cut reaches from the outflow to the trailing edge
+        let cut = &grid.wake_cuts()[0];
+        assert_eq!(cut.lower(), &(0..=24));
+        assert_eq!(cut.upper(), &(grid.nx() - 25..=grid.nx() - 1));
+        for (a, b) in cut.pairs() {
+            let (p, q) = (grid.point(a, 0), grid.point(b, 0));
+            assert_eq!((p.x(), p.y()), (q.x(), q.y()));
+            assert!(p.y().abs() < 1e-12 && p.x() >= 1.0);
+        }
+        let names: Vec<&str> = grid.patches().iter().map(|p| p.name()).collect();
+        assert_eq!(names, ["wall", "farfield", "outflow", "outflow"]);
+        assert_eq!(grid.patches()[0].range(), &(24..=grid.nx() - 25));
+        let te = grid.point(24, 0);
+        assert!((te.x() - 1.0).abs() < 1e-12);
+        assert!((grid.point(0, 0).x() - 6.0).abs() < 1e-9);
+
+        // first cell off the wall follows the normal stretching
+        let (wall, first) = (grid.point(24 + 32, 0), grid.point(24 + 32, 1));
+        let height = (first.x() - wall.x()).hypot(first.y() - wall.y());
+        assert!(height < 1e-2, "first cell height {}", height);
+    }
+
+    #[test]
+    fn test_c_grid_around_generated_naca_section() {
+        use crate::utils::geometry::NacaAirfoil;
+
+        // a cambered section from the naca generator rather than the symmetric one above
+        let mut naca = NacaAirfoil::new("2412", 41).unwrap();
+        naca.set_closed_trailing_edge(true);
+        let body = naca.generate().points().to_vec();
+        let grid = CGridGenerator::new(body.clone(), 11, 17).unwrap().generate().unwrap();
+
+        assert_eq!(grid.nx(), 2 * 10 + body.len());
+        assert!(min_cell_area(&grid) > 0.0);
+        let te = grid.point(10, 0);
+        assert_eq!((te.x(), te.y()), (body[0].x(), body[0].y()));
+        // the cut leaves the trailing edge along the chord line
+        for (a, b) in grid.wake_cuts()[0].pairs() {
+            assert_eq!(grid.point(a, 0).y(), grid.point(b, 0).y());
+            assert!(grid.point(a, 0).x() >= 1.0);
+        }
+    }
+
+    #[test]
+    fn test_c_grid_either_orientation() {
+        let mut body = naca0012(41);
+        let a = CGridGenerator::new(body.clone(), 11, 9).unwrap().generate().unwrap();
+        body.reverse();
+        let b = CGridGenerator::new(body, 11, 9).unwrap().generate().unwrap();
+        for (p, q) in a.points().iter().zip(b.points()) {
+            assert!((p.x() - q.x()).abs() < 1e-12 && (p.y() - q.y()).abs() < 1e-12);
+        }
+    }
+
+    fn circle(radius: f64, n: usize) -> Vec<Cartesian2D> {
+        (0..=n)
+            .map(|k| {
+                let theta = 2.0 * std::f64::consts::PI * (k % n) as f64 / n as f64;
+                Cartesian2D::new(radius * theta.cos(), radius * theta.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_o_grid_cylinder() {
+        let mut generator = OGridGenerator::new(circle(0.5, 48), 21).unwrap();
+        generator.set_farfield_radius(20.0).unwrap();
+        let grid = generator.generate().unwrap();
+
+        assert_eq!((grid.nx(), grid.ny()), (49, 21));
+        assert!(grid.is_periodic());
+        assert_eq!(grid.patches().len(), 2);
+        assert!(min_cell_area(&grid) > 0.0);
+        for j in 0..grid.ny() {
+            let (first, last) = (grid.point(0, j), grid.point(grid.nx() - 1, j));
+            assert_eq!((first.x(), first.y()), (last.x(), last.y()));
+
+            // concentric rings out to the far field
+            let radii: Vec<f64> = (0..grid.nx()).map(|i| grid.point(i, j).x().hypot(grid.point(i, j).y())).collect();
+            let (min, max) = radii.iter().fold((f64::INFINITY, 0.0_f64), |(lo, hi), &r| (lo.min(r), hi.max(r)));
+            assert!(max - min < 1e-9 * max);
+        }
+        let far = grid.point(10, grid.ny() - 1);
+        assert!((far.x().hypot(far.y()) - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_o_grid_seam_is_smoothed_as_interior() {
+        use crate::generator::elliptic::EllipticGenerator;
+
+        // knock the seam off its radial line, a fixed seam would keep the kink
+        let mut grid = OGridGenerator::new(circle(0.5, 32), 13).unwrap().generate().unwrap();
+        let (nx, j) = (grid.nx(), 6);
+        let p = *grid.point(0, j);
+        for i in [0, nx - 1] {
+            grid.set_point(i, j, p.x(), p.y() + 0.3 * p.x());
+        }
+
+        let mut elliptic = EllipticGenerator::new(grid, 1e-10).unwrap();
+        elliptic.solve().unwrap();
+        let smoothed = elliptic.grid();
+
+        assert!(smoothed.is_periodic());
+        assert!(min_cell_area(smoothed) > 0.0);
+        let (first, last) = (smoothed.point(0, j), smoothed.point(nx - 1, j));
+        assert_eq!((first.x(), first.y()), (last.x(), last.y()));
+        // laplace smoothing of a symmetric annulus puts the seam back on the x axis
+        assert!(first.y().abs() < 1e-3 * first.x(), "seam point at {:?}", (first.x(), first.y()));
+    }
+
+    #[test]
+    fn test_c_grid_rejects_blunt_trailing_edge() {
+        let mut body = naca0012(41);
+        body.pop();
+        assert!(CGridGenerator::new(body.clone(), 11, 9).is_err());
+        assert!(OGridGenerator::new(body, 9).is_err());
+    }
+}
@@ -1,3 +1,7 @@
+#![allow(dead_code)]
+
+use std::ops::RangeInclusive;
+
 #[derive(Debug, Clone, Copy)]
 pub struct GridPoint2D {
     i: usize, 
@@ -10,18 +14,253 @@ impl GridPoint2D {
     pub fn new(i: usize, j: usize, x: f64, y: f64) -> Self {
         GridPoint2D { i, j, x, y }
     }
+
+    pub fn i(&self) -> usize { self.i }
+    pub fn j(&self) -> usize { self.j }
+    pub fn x(&self) -> f64 { self.x }
+    pub fn y(&self) -> f64 { self.y }
+}
+
+// the four boundaries of a grid, named the same way as the tfi boundaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Bottom, // j = 0
+    Top,    // j = ny - 1
+    Left,   // i = 0
+    Right,  // i = nx - 1
+}
+
+// two stretches of the j = 0 line that coincide point for point with the indices running
+// in opposite directions, like the two sides of a c-grid wake cut. solvers should treat the
+// cut as an internal interface rather than a boundary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WakeCut {
+    lower: RangeInclusive<usize>,
+    upper: RangeInclusive<usize>,
+}
+
+impl WakeCut {
+    pub fn new(lower: RangeInclusive<usize>, upper: RangeInclusive<usize>) -> Result<Self, &'static str> {
+        if lower.is_empty() || upper.is_empty() {
+            return Err("wake cut ranges must not be empty");
+        }
+        if lower.end() - lower.start() != upper.end() - upper.start() {
+            return Err("wake cut ranges must be the same length");
+        }
+        if lower.end() >= upper.start() {
+            return Err("wake cut lower range must come before the upper range");
+        }
+        Ok(WakeCut { lower, upper })
+    }
+
+    pub fn lower(&self) -> &RangeInclusive<usize> {
+        &self.lower
+    }
+
+    pub fn upper(&self) -> &RangeInclusive<usize> {
+        &self.upper
+    }
+
+    // matching (lower i, upper i) pairs, the first pair is the outer end of the cut
+    pub fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.lower.clone().zip(self.upper.clone().rev())
+    }
+}
+
+// a named stretch of one side of the grid, like a wall or the far field, for the boundary
+// conditions of exported meshes. the range counts points along the side
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundaryPatch {
+    name: String,
+    side: Side,
+    range: RangeInclusive<usize>,
+}
+
+impl BoundaryPatch {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn range(&self) -> &RangeInclusive<usize> {
+        &self.range
+    }
+
+    // (i, j) of the ends of each boundary edge in the patch
+    pub fn edges<'a>(&'a self, grid: &'a Grid2D) -> impl Iterator<Item = ((usize, usize), (usize, usize))> + 'a {
+        (*self.range.start()..*self.range.end())
+            .map(move |k| (grid.side_index(self.side, k), grid.side_index(self.side, k + 1)))
+    }
+}
+
+// where a field's values live: one per grid point, or one per cell with cell (i, j) having
+// points (i, j) and (i + 1, j + 1) as opposite corners
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldLocation {
+    Node,
+    Cell,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldData {
+    Scalar(Vec<f64>),
+    Vector(Vec<[f64; 2]>),
+}
+
+// named values attached to a grid, like quality metrics or jacobians, stored i-fastest in
+// the same order as the points
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    name: String,
+    location: FieldLocation,
+    data: FieldData,
+}
+
+impl Field {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn location(&self) -> FieldLocation {
+        self.location
+    }
+
+    pub fn data(&self) -> &FieldData {
+        &self.data
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.data {
+            FieldData::Scalar(values) => values.len(),
+            FieldData::Vector(values) => values.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Grid2D {
     nx: usize, 
     ny: usize,
     points: Vec<GridPoint2D>, // vec containing all grid points
+    wake_cuts: Vec<WakeCut>,
+    periodic: bool, // i direction wraps round, column nx - 1 duplicates column 0
+    fields: Vec<Field>,
+    patches: Vec<BoundaryPatch>,
 }
 
 impl Grid2D {
+    // relative tolerance for points that should coincide across a cut
+    const CUT_TOL: f64 = 1e-10;
+
     pub fn new(nx: usize, ny: usize) -> Self {
-        Grid2D { nx, ny, points: Vec::with_capacity(nx * ny) }
+        Grid2D { nx, ny, points: Vec::with_capacity(nx * ny), wake_cuts: Vec::new(), periodic: false, fields: Vec::new(), patches: Vec::new() }
+    }
+
+    // records a wake cut, checking that its points coincide if the grid is already filled
+    pub fn add_wake_cut(&mut self, cut: WakeCut) -> Result<(), &'static str> {
+        if *cut.upper.end() >= self.nx {
+            return Err("wake cut range is outside the grid");
+        }
+        if self.is_complete() {
+            let (min_x, max_x, min_y, max_y) = self.extents();
+            let tol = Self::CUT_TOL * (max_x - min_x).hypot(max_y - min_y);
+            for (a, b) in cut.pairs() {
+                let (p, q) = (self.point(a, 0), self.point(b, 0));
+                if (p.x - q.x).hypot(p.y - q.y) > tol {
+                    return Err("wake cut points do not coincide");
+                }
+            }
+        }
+        self.wake_cuts.push(cut);
+        Ok(())
+    }
+
+    pub fn wake_cuts(&self) -> &[WakeCut] {
+        &self.wake_cuts
+    }
+
+    // marks the i = 0 and i = nx - 1 columns as one periodic seam, like an o-grid round a
+    // closed body. checks the columns coincide if the grid is already filled
+    pub fn set_periodic(&mut self, periodic: bool) -> Result<(), &'static str> {
+        if periodic && self.nx < 3 {
+            return Err("periodic grid needs at least three points round the seam");
+        }
+        if periodic && self.is_complete() {
+            let (min_x, max_x, min_y, max_y) = self.extents();
+            let tol = Self::CUT_TOL * (max_x - min_x).hypot(max_y - min_y);
+            for j in 0..self.ny {
+                let (p, q) = (self.point(0, j), self.point(self.nx - 1, j));
+                if (p.x - q.x).hypot(p.y - q.y) > tol {
+                    return Err("periodic seam columns do not coincide");
+                }
+            }
+        }
+        self.periodic = periodic;
+        Ok(())
+    }
+
+    pub fn is_periodic(&self) -> bool {
+        self.periodic
+    }
+
+    // names a stretch of one side, several patches may share a name but not overlap
+    pub fn add_patch(&mut self, name: &str, side: Side, range: RangeInclusive<usize>) -> Result<(), &'static str> {
+        if name.trim().is_empty() {
+            return Err("patch name must not be empty");
+        }
+        if range.start() >= range.end() {
+            return Err("patch must cover at least one edge");
+        }
+        if *range.end() >= self.side_len(side) {
+            return Err("patch range is outside the grid side");
+        }
+        let overlaps = self
+            .patches
+            .iter()
+            .any(|p| p.side == side && p.range.start() < range.end() && range.start() < p.range.end());
+        if overlaps {
+            return Err("boundary patches must not overlap");
+        }
+        self.patches.push(BoundaryPatch { name: name.to_string(), side, range });
+        Ok(())
+    }
+
+    pub fn patches(&self) -> &[BoundaryPatch] {
+        &self.patches
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.nx.saturating_sub(1) * self.ny.saturating_sub(1)
+    }
+
+    // attaches a field, replacing any field of the same name. it must have one value per
+    // node or per cell
+    pub fn add_field(&mut self, name: &str, location: FieldLocation, data: FieldData) -> Result<(), &'static str> {
+        if name.trim().is_empty() {
+            return Err("field name must not be empty");
+        }
+        let field = Field { name: name.to_string(), location, data };
+        let expected = match location {
+            FieldLocation::Node => self.nx * self.ny,
+            FieldLocation::Cell => self.num_cells(),
+        };
+        if field.len() != expected {
+            return Err("field must have one value per node or cell");
+        }
+        self.fields.retain(|f| f.name != name);
+        self.fields.push(field);
+        Ok(())
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.name == name)
     }
 
     pub fn add_point(&mut self, x: f64, y: f64) {
@@ -38,11 +277,58 @@ impl Grid2D {
         self.points.len()
     }
 
+    pub fn nx(&self) -> usize {
+        self.nx
+    }
+
+    pub fn ny(&self) -> usize {
+        self.ny
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.points.len() == self.nx * self.ny
+    }
+
+    pub fn point(&self, i: usize, j: usize) -> &GridPoint2D {
+        // points are stored i-fastest, the same order add_point fills them in
+        &self.points[j * self.nx + i]
+    }
+
+    pub fn set_point(&mut self, i: usize, j: usize, x: f64, y: f64) {
+        let index = j * self.nx + i;
+        self.points[index] = GridPoint2D::new(i, j, x, y);
+    }
+
+    // number of points along one side of the grid
+    pub fn side_len(&self, side: Side) -> usize {
+        match side {
+            Side::Bottom | Side::Top => self.nx,
+            Side::Left | Side::Right => self.ny,
+        }
+    }
+
+    // (i, j) of the k-th point along a side, counting in the direction the index increases
+    pub fn side_index(&self, side: Side, k: usize) -> (usize, usize) {
+        match side {
+            Side::Bottom => (k, 0),
+            Side::Top => (k, self.ny - 1),
+            Side::Left => (0, k),
+            Side::Right => (self.nx - 1, k),
+        }
+    }
+
+    pub fn points(&self) -> &[GridPoint2D] {
+        &self.points
+    }
+
     pub fn extents(&self) -> (f64, f64, f64, f64) {
-        let mut min_x: f64 = 0.0;
-        let mut max_x: f64 = 0.0;
-        let mut min_y: f64 = 0.0;
-        let mut max_y: f64 = 0.0;
+        // start from the first point so grids that don't contain the origin come out right
+        let (mut min_x, mut min_y) = match self.points.first() {
+            Some(point) => (point.x, point.y),
+            None => (0.0, 0.0),
+        };
+        let mut max_x: f64 = min_x;
+        let mut max_y: f64 = min_y;
 
         for point in self.points.iter() {
             if point.x < min_x {
@@ -0,0 +1,141 @@
+#![allow(dead_code)]
+
+use std::io::Read;
+use std::path::Path;
+
+use crate::utils::geometry::{Airfoil, Cartesian2D};
+
+// airfoil coordinate stuff //
+// the two layouts used by the uiuc database. selig files give a name line and then one loop
+// of x y pairs from the trailing edge round to the trailing edge. lednicer files give a name
+// line, a line with the number of upper and lower points, then the upper surface and the
+// lower surface as separate blocks, both from the leading edge to the trailing edge. the
+// layout is worked out from the file, and some selig files leave out the name line
+
+pub fn read(reader: &mut impl Read) -> Result<Airfoil, &'static str> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text).map_err(|_| "failed to read airfoil file")?;
+    parse(&text)
+}
+
+pub fn read_file(path: impl AsRef<Path>) -> Result<Airfoil, &'static str> {
+    read(&mut super::open(path)?)
+}
+
+fn parse(text: &str) -> Result<Airfoil, &'static str> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty()).peekable();
+    let name = match lines.peek() {
+        Some(line) if pair(line).is_none() => lines.next().unwrap_or_default(),
+        Some(_) => "",
+        None => return Err("airfoil file is empty"),
+    };
+    let pairs = lines
+        .map(|line| pair(line).ok_or("airfoil coordinates must be two numbers per line"))
+        .collect::<Result<Vec<(f64, f64)>, &'static str>>()?;
+
+    let points = match lednicer_counts(&pairs) {
+        Some((upper, lower)) => {
+            let (upper, lower) = (&pairs[1..=upper], &pairs[upper + 1..upper + 1 + lower]);
+            // both blocks usually start from the same leading edge point
+            let skip = usize::from(upper[0] == lower[0]);
+            upper.iter().rev().chain(lower[skip..].iter()).copied().collect()
+        }
+        None => pairs,
+    };
+    Airfoil::new(name, points.into_iter().map(|(x, y)| Cartesian2D::new(x, y)).collect())
+}
+
+fn pair(line: &str) -> Option<(f64, f64)> {
+    let mut values = line.split_whitespace().map(str::parse::<f64>);
+    match (values.next(), values.next(), values.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Some((x, y)),
+        _ => None,
+    }
+}
+
+// lednicer files start with the point counts of the two surfaces, which no coordinate of a
+// unit chord airfoil can be mistaken for
+fn lednicer_counts(pairs: &[(f64, f64)]) -> Option<(usize, usize)> {
+    let &(upper, lower) = pairs.first()?;
+    let count = |v: f64| (v >= 2.0 && v.fract() == 0.0).then_some(v as usize);
+    let (upper, lower) = (count(upper)?, count(lower)?);
+    (upper + lower == pairs.len() - 1).then_some((upper, lower))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::geometry::TrailingEdge;
+
+    // a coarse symmetric section, upper surface from the leading edge to the trailing edge
+    const UPPER: [(f64, f64); 5] = [(0.0, 0.0), (0.1, 0.05), (0.3, 0.06), (0.7, 0.03), (1.0, 0.0)];
+
+    fn selig(te: f64) -> String {
+        let mut text = String::from("TEST AIRFOIL\n");
+        for &(x, y) in UPPER.iter().rev() {
+            text += &format!("  {:.6}  {:.6}\n", x, y + te * x);
+        }
+        for &(x, y) in UPPER.iter().skip(1) {
+            text += &format!("  {:.6}  {:.6}\n", x, -y - te * x);
+        }
+        text
+    }
+
+    #[test]
+    fn test_selig_sharp_trailing_edge() {
+        let airfoil = parse(&selig(0.0)).unwrap();
+        assert_eq!(airfoil.name(), "TEST AIRFOIL");
+        assert_eq!(airfoil.trailing_edge(), TrailingEdge::Sharp);
+        assert_eq!(airfoil.points().len(), 9);
+        assert_eq!(airfoil.points()[0], airfoil.points()[8]);
+        assert_eq!(airfoil.leading_edge_index(), 4);
+        assert!(airfoil.points()[2].y() > 0.0);
+    }
+
+    #[test]
+    fn test_lednicer_matches_selig() {
+        let mut text = String::from("TEST AIRFOIL\n       5.       5.\n\n");
+        for &(x, y) in UPPER.iter() {
+            text += &format!(" {:.6} {:.6}\n", x, y + 0.002 * x);
+        }
+        text += "\n";
+        for &(x, y) in UPPER.iter() {
+            text += &format!(" {:.6} {:.6}\n", x, -y - 0.002 * x);
+        }
+        let lednicer = parse(&text).unwrap();
+        let selig = parse(&selig(0.002)).unwrap();
+
+        assert_eq!(lednicer.trailing_edge(), TrailingEdge::Blunt);
+        assert!((lednicer.trailing_edge_thickness() - 0.004).abs() < 1e-9);
+        assert_eq!(lednicer.points().len(), selig.points().len());
+        for (a, b) in lednicer.points().iter().zip(selig.points()) {
+            assert!(a.distance(b) < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_normalises_chord_and_orientation() {
+        // clockwise, chord 2 shifted along x, no name line
+        let mut text = String::new();
+        for &(x, y) in UPPER.iter().rev() {
+            text += &format!("{} {}\n", 2.0 * x + 3.0, -2.0 * y);
+        }
+        for &(x, y) in UPPER.iter().skip(1) {
+            text += &format!("{} {}\n", 2.0 * x + 3.0, 2.0 * y);
+        }
+        let airfoil = parse(&text).unwrap();
+        assert_eq!(airfoil.name(), "");
+        let upper = airfoil.upper();
+        for (p, &(x, y)) in upper.iter().zip(UPPER.iter().rev()) {
+            assert!((p.x() - x).abs() < 1e-12 && (p.y() - y).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_rejects_duplicated_points() {
+        let point = "  0.300000  0.060000\n";
+        let text = selig(0.0).replacen(point, &point.repeat(2), 1);
+        assert_eq!(parse(&text).unwrap_err(), "airfoil has duplicated points");
+        assert!(parse("name\n0 0\n1 x\n").is_err());
+    }
+}
@@ -0,0 +1,382 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::grid::Grid2D;
+use crate::mesh::{Element, Mesh2D};
+use crate::multiblock::MultiBlockGrid2D;
+use crate::utils::geometry::Cartesian2D;
+
+// gmsh stuff //
+// ascii msh 4.1 files. on export every element goes in one surface entity, in the physical
+// surface "domain", and each named boundary patch becomes a curve entity of line elements
+// in a physical curve of the same name. on import triangles and quads are read from any
+// entity and line elements become patches named after their physical curves
+
+// physical group holding the 2d elements of an exported mesh
+const DOMAIN: &str = "domain";
+
+// gmsh element type numbers
+const LINE: usize = 1;
+const TRIANGLE: usize = 2;
+const QUAD: usize = 3;
+const POINT: usize = 15;
+
+pub fn write(writer: &mut impl Write, mesh: &Mesh2D) -> Result<(), &'static str> {
+    msh(writer, mesh).map_err(|_| "failed to write gmsh file")
+}
+
+pub fn write_grid(writer: &mut impl Write, grid: &Grid2D) -> Result<(), &'static str> {
+    write(writer, &Mesh2D::from_grid(grid)?)
+}
+
+pub fn write_multiblock(writer: &mut impl Write, grid: &MultiBlockGrid2D) -> Result<(), &'static str> {
+    write(writer, &Mesh2D::from_multiblock(grid)?)
+}
+
+pub fn write_file(path: impl AsRef<Path>, mesh: &Mesh2D) -> Result<(), &'static str> {
+    let mut writer = super::create(path)?;
+    write(&mut writer, mesh)?;
+    writer.flush().map_err(|_| "failed to write gmsh file")
+}
+
+pub fn read(reader: &mut impl Read) -> Result<Mesh2D, &'static str> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text).map_err(|_| "failed to read gmsh file")?;
+    parse(&text)
+}
+
+pub fn read_file(path: impl AsRef<Path>) -> Result<Mesh2D, &'static str> {
+    read(&mut super::open(path)?)
+}
+
+fn msh(writer: &mut impl Write, mesh: &Mesh2D) -> std::io::Result<()> {
+    let patches = mesh.patches();
+    let bounds = |nodes: &mut dyn Iterator<Item = usize>| -> String {
+        let (mut min, mut max) = ((f64::INFINITY, f64::INFINITY), (f64::NEG_INFINITY, f64::NEG_INFINITY));
+        for n in nodes {
+            let p = mesh.nodes()[n];
+            min = (min.0.min(p.x()), min.1.min(p.y()));
+            max = (max.0.max(p.x()), max.1.max(p.y()));
+        }
+        if min.0 > max.0 {
+            return "0 0 0 0 0 0".to_string();
+        }
+        format!("{:e} {:e} 0 {:e} {:e} 0", min.0, min.1, max.0, max.1)
+    };
+
+    writeln!(writer, "$MeshFormat")?;
+    writeln!(writer, "4.1 0 8")?;
+    writeln!(writer, "$EndMeshFormat")?;
+
+    writeln!(writer, "$PhysicalNames")?;
+    writeln!(writer, "{}", patches.len() + 1)?;
+    for (k, patch) in patches.iter().enumerate() {
+        writeln!(writer, "1 {} \"{}\"", k + 1, patch.name().replace('"', "'"))?;
+    }
+    writeln!(writer, "2 1 \"{}\"", DOMAIN)?;
+    writeln!(writer, "$EndPhysicalNames")?;
+
+    // curve k + 1 is in physical curve k + 1, surface 1 in physical surface 1
+    writeln!(writer, "$Entities")?;
+    writeln!(writer, "0 {} 1 0", patches.len())?;
+    for (k, patch) in patches.iter().enumerate() {
+        let box_ = bounds(&mut patch.edges().iter().flatten().copied());
+        writeln!(writer, "{} {} 1 {} 0", k + 1, box_, k + 1)?;
+    }
+    writeln!(writer, "1 {} 1 1 0", bounds(&mut (0..mesh.num_nodes())))?;
+    writeln!(writer, "$EndEntities")?;
+
+    let n = mesh.num_nodes();
+    writeln!(writer, "$Nodes")?;
+    writeln!(writer, "1 {} 1 {}", n, n)?;
+    writeln!(writer, "2 1 0 {}", n)?;
+    for tag in 1..=n {
+        writeln!(writer, "{}", tag)?;
+    }
+    for p in mesh.nodes() {
+        writeln!(writer, "{:e} {:e} 0", p.x(), p.y())?;
+    }
+    writeln!(writer, "$EndNodes")?;
+
+    // one element block per patch, then the triangles and quads
+    let triangles: Vec<&Element> = mesh.elements().iter().filter(|e| matches!(e, Element::Triangle(_))).collect();
+    let quads: Vec<&Element> = mesh.elements().iter().filter(|e| matches!(e, Element::Quad(_))).collect();
+    let mut blocks: Vec<(usize, usize, usize, Vec<&[usize]>)> = Vec::new();
+    for (k, patch) in patches.iter().enumerate() {
+        blocks.push((1, k + 1, LINE, patch.edges().iter().map(|e| &e[..]).collect()));
+    }
+    for (kind, elements) in [(TRIANGLE, triangles), (QUAD, quads)] {
+        if !elements.is_empty() {
+            blocks.push((2, 1, kind, elements.iter().map(|e| e.nodes()).collect()));
+        }
+    }
+
+    let total: usize = blocks.iter().map(|block| block.3.len()).sum();
+    writeln!(writer, "$Elements")?;
+    writeln!(writer, "{} {} 1 {}", blocks.len(), total, total)?;
+    let mut tag = 0;
+    for (dim, entity, kind, elements) in blocks {
+        writeln!(writer, "{} {} {} {}", dim, entity, kind, elements.len())?;
+        for nodes in elements {
+            tag += 1;
+            let nodes: Vec<String> = nodes.iter().map(|n| (n + 1).to_string()).collect();
+            writeln!(writer, "{} {}", tag, nodes.join(" "))?;
+        }
+    }
+    writeln!(writer, "$EndElements")?;
+    Ok(())
+}
+
+// whitespace separated tokens of one section
+struct Tokens<'a> {
+    tokens: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> Result<&'a str, &'static str> {
+        self.tokens.next().ok_or("gmsh section ended early")
+    }
+
+    fn usize(&mut self) -> Result<usize, &'static str> {
+        self.next()?.parse().map_err(|_| "expected an integer in gmsh file")
+    }
+
+    fn int(&mut self) -> Result<i64, &'static str> {
+        self.next()?.parse().map_err(|_| "expected an integer in gmsh file")
+    }
+
+    fn float(&mut self) -> Result<f64, &'static str> {
+        self.next()?.parse().map_err(|_| "expected a number in gmsh file")
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), &'static str> {
+        for _ in 0..n {
+            self.next()?;
+        }
+        Ok(())
+    }
+}
+
+// splits the file into its $Section ... $EndSection bodies
+fn sections(text: &str) -> Result<HashMap<&str, &str>, &'static str> {
+    let mut sections = HashMap::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        let after = &rest[start + 1..];
+        let name_end = after.find(char::is_whitespace).unwrap_or(after.len());
+        let name = &after[..name_end];
+        let end_tag = format!("$End{}", name);
+        let body_end = after.find(&end_tag).ok_or("gmsh section is not closed")?;
+        sections.insert(name, &after[name_end..body_end]);
+        rest = &after[body_end + end_tag.len()..];
+    }
+    Ok(sections)
+}
+
+fn parse(text: &str) -> Result<Mesh2D, &'static str> {
+    let sections = sections(text)?;
+
+    let mut format = Tokens { tokens: sections.get("MeshFormat").ok_or("not a gmsh file")?.split_whitespace() };
+    if !format.next()?.starts_with("4.") {
+        return Err("only version 4 gmsh files are supported");
+    }
+    if format.usize()? != 0 {
+        return Err("binary gmsh files are not supported");
+    }
+
+    // physical curve names, one per line as: dim tag "name"
+    let mut names: HashMap<i64, String> = HashMap::new();
+    if let Some(body) = sections.get("PhysicalNames") {
+        for line in body.lines().skip_while(|l| l.trim().is_empty()).skip(1) {
+            let mut parts = line.splitn(3, char::is_whitespace);
+            let (dim, tag, name) = (parts.next(), parts.next(), parts.next());
+            if let (Some("1"), Some(tag), Some(name)) = (dim, tag, name) {
+                let tag = tag.parse().map_err(|_| "bad physical group tag in gmsh file")?;
+                names.insert(tag, name.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+
+    // physical groups of each curve entity
+    let mut curve_groups: HashMap<i64, Vec<i64>> = HashMap::new();
+    if let Some(body) = sections.get("Entities") {
+        let mut tokens = Tokens { tokens: body.split_whitespace() };
+        let counts = [tokens.usize()?, tokens.usize()?, tokens.usize()?, tokens.usize()?];
+        for (dim, &count) in counts.iter().enumerate() {
+            for _ in 0..count {
+                let tag = tokens.int()?;
+                tokens.skip(if dim == 0 { 3 } else { 6 })?;
+                let n_physical = tokens.usize()?;
+                let groups = (0..n_physical).map(|_| tokens.int()).collect::<Result<Vec<i64>, _>>()?;
+                if dim == 1 {
+                    curve_groups.insert(tag, groups);
+                }
+                if dim > 0 {
+                    let n_bounding = tokens.usize()?;
+                    tokens.skip(n_bounding)?;
+                }
+            }
+        }
+    }
+
+    let mut mesh = Mesh2D::new();
+    let mut node_index: HashMap<usize, usize> = HashMap::new();
+    let mut tokens = Tokens { tokens: sections.get("Nodes").ok_or("gmsh file has no nodes")?.split_whitespace() };
+    let n_blocks = tokens.usize()?;
+    tokens.skip(3)?;
+    for _ in 0..n_blocks {
+        let dim = tokens.usize()?;
+        tokens.skip(1)?;
+        let parametric = tokens.usize()? == 1;
+        let count = tokens.usize()?;
+        let tags = (0..count).map(|_| tokens.usize()).collect::<Result<Vec<usize>, _>>()?;
+        for tag in tags {
+            let (x, y) = (tokens.float()?, tokens.float()?);
+            tokens.skip(1 + if parametric { dim } else { 0 })?;
+            node_index.insert(tag, mesh.add_node(Cartesian2D::new(x, y)));
+        }
+    }
+
+    let mut tokens = Tokens { tokens: sections.get("Elements").ok_or("gmsh file has no elements")?.split_whitespace() };
+    let n_blocks = tokens.usize()?;
+    tokens.skip(3)?;
+    let mut patches: Vec<(String, Vec<[usize; 2]>)> = Vec::new();
+    for _ in 0..n_blocks {
+        tokens.skip(1)?;
+        let entity = tokens.int()?;
+        let kind = tokens.usize()?;
+        let count = tokens.usize()?;
+        let n_nodes = match kind {
+            LINE => 2,
+            TRIANGLE => 3,
+            QUAD => 4,
+            POINT => 1,
+            _ => return Err("gmsh file has elements other than lines, triangles and quads"),
+        };
+        for _ in 0..count {
+            tokens.skip(1)?;
+            let mut nodes = [0; 4];
+            for node in nodes.iter_mut().take(n_nodes) {
+                *node = *node_index.get(&tokens.usize()?).ok_or("gmsh element refers to a missing node")?;
+            }
+            match kind {
+                TRIANGLE => mesh.add_element(Element::Triangle([nodes[0], nodes[1], nodes[2]]))?,
+                QUAD => mesh.add_element(Element::Quad(nodes))?,
+                LINE => {
+                    for group in curve_groups.get(&entity).into_iter().flatten() {
+                        let name = names.get(group).cloned().unwrap_or_else(|| format!("physical {}", group));
+                        match patches.iter_mut().find(|(n, _)| *n == name) {
+                            Some((_, edges)) => edges.push([nodes[0], nodes[1]]),
+                            None => patches.push((name, vec![[nodes[0], nodes[1]]])),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    for (name, edges) in patches {
+        mesh.add_patch(&name, edges)?;
+    }
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::topology::OGridGenerator;
+
+    #[test]
+    fn test_round_trip_o_grid() {
+        let body: Vec<Cartesian2D> = (0..=12)
+            .map(|k| {
+                let theta = 2.0 * std::f64::consts::PI * (k % 12) as f64 / 12.0;
+                Cartesian2D::new(theta.cos(), theta.sin())
+            })
+            .collect();
+        let grid = OGridGenerator::new(body, 4).unwrap().generate().unwrap();
+
+        let mut bytes = Vec::new();
+        write_grid(&mut bytes, &grid).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.starts_with("$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$PhysicalNames\n3\n1 1 \"wall\"\n"));
+        assert!(text.contains("2 1 3 36\n"));
+
+        let mesh = read(&mut text.as_bytes()).unwrap();
+        let original = Mesh2D::from_grid(&grid).unwrap();
+        assert_eq!(mesh.num_nodes(), 48);
+        assert_eq!(mesh.elements(), original.elements());
+        assert_eq!(mesh.patches(), original.patches());
+        for (a, b) in mesh.nodes().iter().zip(original.nodes()) {
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_reads_mixed_mesh() {
+        // unit square split into a quad and two triangles, with sparse node tags
+        let text = r#"$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$PhysicalNames
+2
+1 7 "inlet wall"
+2 8 "fluid"
+$EndPhysicalNames
+$Entities
+1 1 1 0
+1 0 0 0 1 0
+3 0 0 0 0 1 0 1 7 2 1 -1
+1 0 0 0 2 1 0 1 8 0
+$EndEntities
+$Nodes
+2 6 1 60
+0 1 0 1
+10
+0 0 0
+2 1 0 5
+20
+30
+40
+50
+60
+1 0 0
+2 0 0
+2 1 0
+1 1 0
+0 1 0
+$EndNodes
+$Elements
+3 4 1 4
+1 3 1 1
+1 10 60
+2 1 3 1
+2 10 20 50 60
+2 1 2 2
+3 20 30 40
+4 20 40 50
+$EndElements
+"#;
+        let mesh = read(&mut text.as_bytes()).unwrap();
+        assert_eq!(mesh.num_nodes(), 6);
+        assert_eq!(mesh.nodes()[5], Cartesian2D::new(0.0, 1.0));
+        assert_eq!(mesh.elements(), [
+            Element::Quad([0, 1, 4, 5]),
+            Element::Triangle([1, 2, 3]),
+            Element::Triangle([1, 3, 4]),
+        ]);
+        assert_eq!(mesh.patches().len(), 1);
+        assert_eq!((mesh.patches()[0].name(), mesh.patches()[0].edges()), ("inlet wall", &[[0, 5]][..]));
+    }
+
+    #[test]
+    fn test_rejects_unsupported_files() {
+        assert!(read(&mut "$MeshFormat\n2.2 0 8\n$EndMeshFormat\n".as_bytes()).is_err());
+        assert!(read(&mut "$MeshFormat\n4.1 1 8\n$EndMeshFormat\n".as_bytes()).is_err());
+        let second_order = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Nodes\n0 0 0 0\n$EndNodes\n$Elements\n1 1 1 1\n2 1 9 0\n$EndElements\n";
+        assert!(read(&mut second_order.as_bytes()).is_err());
+    }
+}
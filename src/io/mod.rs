@@ -0,0 +1,23 @@
+#![allow(dead_code)]
+
+// reading and writing grids in the file formats other tools use. each format lives in its
+// own module and works on any std::io reader or writer, with _file helpers for paths
+pub mod airfoil;
+pub mod gmsh;
+pub mod openfoam;
+pub mod plot3d;
+pub mod su2;
+pub mod tecplot;
+pub mod vtk;
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+pub(crate) fn create(path: impl AsRef<Path>) -> Result<BufWriter<File>, &'static str> {
+    File::create(path).map(BufWriter::new).map_err(|_| "could not create file")
+}
+
+pub(crate) fn open(path: impl AsRef<Path>) -> Result<BufReader<File>, &'static str> {
+    File::open(path).map(BufReader::new).map_err(|_| "could not open file")
+}
@@ -0,0 +1,403 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::grid::Grid2D;
+use crate::mesh::Mesh2D;
+use crate::multiblock::MultiBlockGrid2D;
+
+// openfoam stuff //
+// a 2d mesh extruded one cell thick in z and written as constant/polyMesh under a case
+// directory. the z = 0 and z = thickness faces go in the empty patch "frontAndBack", named
+// boundary patches become patches of their own (walls if the name says so), and any
+// boundary edge not in a named patch ends up in "defaultFaces"
+
+const FRONT_AND_BACK: &str = "frontAndBack";
+const DEFAULT_FACES: &str = "defaultFaces";
+
+pub fn write(case: impl AsRef<Path>, mesh: &Mesh2D, thickness: f64) -> Result<(), &'static str> {
+    let poly_mesh = PolyMesh::new(mesh, thickness)?;
+    let directory = case.as_ref().join("constant").join("polyMesh");
+    fs::create_dir_all(&directory).map_err(|_| "could not create polyMesh directory")?;
+
+    let files: [(&str, FileWriter); 5] = [
+        ("points", PolyMesh::write_points),
+        ("faces", PolyMesh::write_faces),
+        ("owner", PolyMesh::write_owner),
+        ("neighbour", PolyMesh::write_neighbour),
+        ("boundary", PolyMesh::write_boundary),
+    ];
+    for (name, write) in files {
+        let mut writer = super::create(directory.join(name))?;
+        write(&poly_mesh, &mut writer)
+            .and_then(|_| writer.flush())
+            .map_err(|_| "failed to write openfoam mesh")?;
+    }
+    Ok(())
+}
+
+pub fn write_grid(case: impl AsRef<Path>, grid: &Grid2D, thickness: f64) -> Result<(), &'static str> {
+    write(case, &Mesh2D::from_grid(grid)?, thickness)
+}
+
+pub fn write_multiblock(case: impl AsRef<Path>, grid: &MultiBlockGrid2D, thickness: f64) -> Result<(), &'static str> {
+    write(case, &Mesh2D::from_multiblock(grid)?, thickness)
+}
+
+// a boundary face, as its owner cell and point labels
+type BoundaryFace = (usize, Vec<usize>);
+
+// an edge of the 2d mesh as its sorted node pair
+type EdgeKey = (usize, usize);
+
+type FileWriter = fn(&PolyMesh, &mut dyn Write) -> std::io::Result<()>;
+
+struct Patch {
+    name: String,
+    kind: &'static str,
+    n_faces: usize,
+    start_face: usize,
+}
+
+// faces are ordered internal ones first (by owner, then neighbour), then patch by patch
+struct PolyMesh {
+    points: Vec<[f64; 3]>,
+    faces: Vec<Vec<usize>>,
+    owner: Vec<usize>,
+    neighbour: Vec<usize>,
+    patches: Vec<Patch>,
+    n_cells: usize,
+}
+
+impl PolyMesh {
+    fn new(mesh: &Mesh2D, thickness: f64) -> Result<Self, &'static str> {
+        if thickness <= 0.0 {
+            return Err("extrusion thickness must be positive");
+        }
+        if mesh.num_elements() == 0 {
+            return Err("mesh has no cells");
+        }
+        let n = mesh.num_nodes();
+        let mut points: Vec<[f64; 3]> = mesh.nodes().iter().map(|p| [p.x(), p.y(), 0.0]).collect();
+        points.extend(mesh.nodes().iter().map(|p| [p.x(), p.y(), thickness]));
+
+        // each cell's nodes anticlockwise, so an edge a -> b has the cell on its left
+        let cells: Vec<Vec<usize>> = mesh
+            .elements()
+            .iter()
+            .map(|element| {
+                let mut nodes = element.nodes().to_vec();
+                if polygon_area(mesh, &nodes) < 0.0 {
+                    nodes.reverse();
+                }
+                nodes
+            })
+            .collect();
+
+        // cells either side of every edge, keyed by its sorted node pair
+        let mut edges: HashMap<EdgeKey, Vec<(usize, [usize; 2])>> = HashMap::new();
+        for (c, nodes) in cells.iter().enumerate() {
+            for k in 0..nodes.len() {
+                let (a, b) = (nodes[k], nodes[(k + 1) % nodes.len()]);
+                edges.entry((a.min(b), a.max(b))).or_default().push((c, [a, b]));
+            }
+        }
+
+        // a side face a -> b -> b' -> a' points out of the cell on the left of a -> b
+        let side_face = |[a, b]: [usize; 2]| vec![a, b, b + n, a + n];
+
+        let mut internal: Vec<(usize, usize, Vec<usize>)> = Vec::new();
+        let mut boundary: HashMap<EdgeKey, BoundaryFace> = HashMap::new();
+        for (&key, sides) in edges.iter() {
+            match sides.as_slice() {
+                [(c, edge)] => {
+                    boundary.insert(key, (*c, side_face(*edge)));
+                }
+                [(c0, e0), (c1, _)] => {
+                    let (owner, neighbour, edge) = if c0 < c1 { (*c0, *c1, *e0) } else { (*c1, *c0, [e0[1], e0[0]]) };
+                    internal.push((owner, neighbour, side_face(edge)));
+                }
+                _ => return Err("mesh edge is shared by more than two cells"),
+            }
+        }
+        internal.sort_by_key(|&(owner, neighbour, _)| (owner, neighbour));
+
+        let mut poly_mesh = PolyMesh {
+            points,
+            faces: Vec::new(),
+            owner: Vec::new(),
+            neighbour: Vec::new(),
+            patches: Vec::new(),
+            n_cells: cells.len(),
+        };
+        for (owner, neighbour, face) in internal {
+            poly_mesh.faces.push(face);
+            poly_mesh.owner.push(owner);
+            poly_mesh.neighbour.push(neighbour);
+        }
+
+        let add_patch = |poly_mesh: &mut PolyMesh, name: &str, kind: &'static str, faces: Vec<BoundaryFace>| {
+            let start_face = poly_mesh.faces.len();
+            let n_faces = faces.len();
+            for (owner, face) in faces {
+                poly_mesh.faces.push(face);
+                poly_mesh.owner.push(owner);
+            }
+            // patch names are single words in openfoam
+            let name = name.split_whitespace().collect::<Vec<&str>>().join("_");
+            poly_mesh.patches.push(Patch { name, kind, n_faces, start_face });
+        };
+
+        for patch in mesh.patches() {
+            let mut faces = Vec::with_capacity(patch.edges().len());
+            for &[a, b] in patch.edges() {
+                let face = boundary.remove(&(a.min(b), a.max(b))).ok_or("boundary patch edge is not on the mesh boundary")?;
+                faces.push(face);
+            }
+            faces.sort_by_key(|(owner, _)| *owner);
+            let kind = if patch.name().to_lowercase().contains("wall") { "wall" } else { "patch" };
+            add_patch(&mut poly_mesh, patch.name(), kind, faces);
+        }
+
+        if !boundary.is_empty() {
+            let mut faces: Vec<(EdgeKey, BoundaryFace)> = boundary.into_iter().collect();
+            faces.sort_by_key(|(key, (owner, _))| (*owner, *key));
+            add_patch(&mut poly_mesh, DEFAULT_FACES, "patch", faces.into_iter().map(|(_, face)| face).collect());
+        }
+
+        // front faces look down -z, back faces up +z
+        let mut faces: Vec<BoundaryFace> = cells.iter().enumerate().map(|(c, nodes)| (c, nodes.iter().rev().copied().collect())).collect();
+        faces.extend(cells.iter().enumerate().map(|(c, nodes)| (c, nodes.iter().map(|&k| k + n).collect())));
+        add_patch(&mut poly_mesh, FRONT_AND_BACK, "empty", faces);
+
+        Ok(poly_mesh)
+    }
+
+    fn header(writer: &mut dyn Write, class: &str, object: &str, note: Option<String>) -> std::io::Result<()> {
+        writeln!(writer, "FoamFile")?;
+        writeln!(writer, "{{")?;
+        writeln!(writer, "    version     2.0;")?;
+        writeln!(writer, "    format      ascii;")?;
+        writeln!(writer, "    class       {};", class)?;
+        if let Some(note) = note {
+            writeln!(writer, "    note        \"{}\";", note)?;
+        }
+        writeln!(writer, "    location    \"constant/polyMesh\";")?;
+        writeln!(writer, "    object      {};", object)?;
+        writeln!(writer, "}}")?;
+        writeln!(writer)
+    }
+
+    fn write_points(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        Self::header(writer, "vectorField", "points", None)?;
+        writeln!(writer, "{}", self.points.len())?;
+        writeln!(writer, "(")?;
+        for p in self.points.iter() {
+            writeln!(writer, "({:e} {:e} {:e})", p[0], p[1], p[2])?;
+        }
+        writeln!(writer, ")")
+    }
+
+    fn write_faces(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        Self::header(writer, "faceList", "faces", None)?;
+        writeln!(writer, "{}", self.faces.len())?;
+        writeln!(writer, "(")?;
+        for face in self.faces.iter() {
+            let labels: Vec<String> = face.iter().map(|k| k.to_string()).collect();
+            writeln!(writer, "{}({})", face.len(), labels.join(" "))?;
+        }
+        writeln!(writer, ")")
+    }
+
+    fn write_labels(&self, writer: &mut dyn Write, object: &str, labels: &[usize]) -> std::io::Result<()> {
+        let note = format!(
+            "nPoints:{} nCells:{} nFaces:{} nInternalFaces:{}",
+            self.points.len(),
+            self.n_cells,
+            self.faces.len(),
+            self.neighbour.len()
+        );
+        Self::header(writer, "labelList", object, Some(note))?;
+        writeln!(writer, "{}", labels.len())?;
+        writeln!(writer, "(")?;
+        for label in labels {
+            writeln!(writer, "{}", label)?;
+        }
+        writeln!(writer, ")")
+    }
+
+    fn write_owner(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        self.write_labels(writer, "owner", &self.owner)
+    }
+
+    fn write_neighbour(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        self.write_labels(writer, "neighbour", &self.neighbour)
+    }
+
+    fn write_boundary(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        Self::header(writer, "polyBoundaryMesh", "boundary", None)?;
+        writeln!(writer, "{}", self.patches.len())?;
+        writeln!(writer, "(")?;
+        for patch in self.patches.iter() {
+            writeln!(writer, "    {}", patch.name)?;
+            writeln!(writer, "    {{")?;
+            writeln!(writer, "        type            {};", patch.kind)?;
+            if patch.kind != "patch" {
+                writeln!(writer, "        inGroups        1({});", patch.kind)?;
+            }
+            writeln!(writer, "        nFaces          {};", patch.n_faces)?;
+            writeln!(writer, "        startFace       {};", patch.start_face)?;
+            writeln!(writer, "    }}")?;
+        }
+        writeln!(writer, ")")
+    }
+}
+
+fn polygon_area(mesh: &Mesh2D, nodes: &[usize]) -> f64 {
+    let p = mesh.nodes();
+    (0..nodes.len())
+        .map(|k| {
+            let (a, b) = (p[nodes[k]], p[nodes[(k + 1) % nodes.len()]]);
+            0.5 * (a.x() * b.y() - b.x() * a.y())
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::topology::CGridGenerator;
+    use crate::mesh::Element;
+    use crate::utils::geometry::Cartesian2D;
+
+    fn centre(poly_mesh: &PolyMesh, nodes: &[usize]) -> [f64; 3] {
+        let mut c = [0.0; 3];
+        for &k in nodes {
+            for (c, p) in c.iter_mut().zip(poly_mesh.points[k]) {
+                *c += p / nodes.len() as f64;
+            }
+        }
+        c
+    }
+
+    // area vector by the right hand rule, the way openfoam orients faces
+    fn normal(poly_mesh: &PolyMesh, face: &[usize]) -> [f64; 3] {
+        let c = centre(poly_mesh, face);
+        let mut s = [0.0; 3];
+        for k in 0..face.len() {
+            let (a, b) = (poly_mesh.points[face[k]], poly_mesh.points[face[(k + 1) % face.len()]]);
+            let (a, b) = ([a[0] - c[0], a[1] - c[1], a[2] - c[2]], [b[0] - c[0], b[1] - c[1], b[2] - c[2]]);
+            s[0] += 0.5 * (a[1] * b[2] - a[2] * b[1]);
+            s[1] += 0.5 * (a[2] * b[0] - a[0] * b[2]);
+            s[2] += 0.5 * (a[0] * b[1] - a[1] * b[0]);
+        }
+        s
+    }
+
+    fn check_orientation(poly_mesh: &PolyMesh, mesh: &Mesh2D) {
+        let cell_centre = |c: usize| {
+            let nodes = mesh.elements()[c].nodes();
+            let mut all: Vec<usize> = nodes.to_vec();
+            all.extend(nodes.iter().map(|k| k + mesh.num_nodes()));
+            centre(poly_mesh, &all)
+        };
+        for (f, face) in poly_mesh.faces.iter().enumerate() {
+            let s = normal(poly_mesh, face);
+            let from = cell_centre(poly_mesh.owner[f]);
+            let to = match poly_mesh.neighbour.get(f) {
+                Some(&neighbour) => cell_centre(neighbour),
+                None => centre(poly_mesh, face),
+            };
+            let dot: f64 = (0..3).map(|d| s[d] * (to[d] - from[d])).sum();
+            assert!(dot > 0.0, "face {} points into its owner", f);
+            if let Some(&neighbour) = poly_mesh.neighbour.get(f) {
+                assert!(poly_mesh.owner[f] < neighbour);
+            }
+        }
+    }
+
+    #[test]
+    fn test_c_grid_extrusion() {
+        let body: Vec<Cartesian2D> = (0..=20)
+            .map(|k| {
+                let theta = 2.0 * std::f64::consts::PI * (k % 20) as f64 / 20.0;
+                Cartesian2D::new(theta.cos(), 0.2 * theta.sin())
+            })
+            .collect();
+        let grid = CGridGenerator::new(body, 6, 5).unwrap().generate().unwrap();
+        let mesh = Mesh2D::from_grid(&grid).unwrap();
+        let poly_mesh = PolyMesh::new(&mesh, 0.1).unwrap();
+
+        let (nx, ny) = (grid.nx(), grid.ny());
+        let n_cells = (nx - 1) * (ny - 1);
+        assert_eq!(poly_mesh.n_cells, n_cells);
+        assert_eq!(poly_mesh.points.len(), 2 * mesh.num_nodes());
+        // the wake cut edges are internal faces, not boundary
+        let n_internal = (nx - 2) * (ny - 1) + (nx - 1) * (ny - 2) + 5;
+        assert_eq!(poly_mesh.neighbour.len(), n_internal);
+        for pair in poly_mesh.owner[..n_internal].windows(2) {
+            assert!(pair[0] <= pair[1]);
+        }
+
+        let patches: Vec<(&str, &str, usize)> = poly_mesh.patches.iter().map(|p| (p.name.as_str(), p.kind, p.n_faces)).collect();
+        assert_eq!(patches, [
+            ("wall", "wall", 20),
+            ("farfield", "patch", nx - 1),
+            ("outflow", "patch", 2 * (ny - 1)),
+            ("frontAndBack", "empty", 2 * n_cells),
+        ]);
+        let last = poly_mesh.patches.last().unwrap();
+        assert_eq!(last.start_face + last.n_faces, poly_mesh.faces.len());
+        check_orientation(&poly_mesh, &mesh);
+    }
+
+    #[test]
+    fn test_clockwise_triangles_and_default_faces() {
+        let mut mesh = Mesh2D::new();
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            mesh.add_node(Cartesian2D::new(x, y));
+        }
+        mesh.add_element(Element::Triangle([0, 2, 1])).unwrap();
+        mesh.add_element(Element::Triangle([0, 3, 2])).unwrap();
+        mesh.add_patch("inlet", vec![[3, 0]]).unwrap();
+
+        let poly_mesh = PolyMesh::new(&mesh, 1.0).unwrap();
+        assert_eq!(poly_mesh.neighbour, [1]);
+        let names: Vec<&str> = poly_mesh.patches.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["inlet", "defaultFaces", "frontAndBack"]);
+        assert_eq!(poly_mesh.patches[1].n_faces, 3);
+        check_orientation(&poly_mesh, &mesh);
+
+        mesh.add_patch("bad", vec![[0, 2]]).unwrap();
+        assert!(PolyMesh::new(&mesh, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_writes_poly_mesh_directory() {
+        let mut grid = Grid2D::new(3, 2);
+        for j in 0..2 {
+            for i in 0..3 {
+                grid.add_point(i as f64, j as f64);
+            }
+        }
+        grid.add_patch("lower wall", crate::grid::Side::Bottom, 0..=2).unwrap();
+
+        let case = std::env::temp_dir().join(format!("openfoam_export_{}", std::process::id()));
+        write_grid(&case, &grid, 0.5).unwrap();
+        let directory = case.join("constant").join("polyMesh");
+        let read = |name: &str| fs::read_to_string(directory.join(name)).unwrap();
+
+        let points = read("points");
+        assert!(points.contains("class       vectorField;"));
+        assert!(points.contains("\n12\n(\n(0e0 0e0 0e0)\n"));
+        assert!(read("owner").contains("note        \"nPoints:12 nCells:2 nFaces:11 nInternalFaces:1\";"));
+        assert!(read("faces").contains("\n11\n(\n4(1 4 10 7)\n"));
+        let boundary = read("boundary");
+        assert!(boundary.contains("    lower_wall\n    {\n        type            wall;\n        inGroups        1(wall);\n        nFaces          2;\n        startFace       1;\n"));
+        assert!(boundary.contains("        type            empty;\n        inGroups        1(empty);\n        nFaces          4;\n        startFace       7;\n"));
+        fs::remove_dir_all(&case).unwrap();
+    }
+}
@@ -0,0 +1,526 @@
+#![allow(dead_code)]
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::grid::Grid2D;
+
+// plot3d stuff //
+// grid files as read by the legacy nasa solvers. a multi-block file starts with the number
+// of blocks, then the dimensions of every block, then each block's coordinates with i
+// running fastest. the whole 2d layout gives (ni, nj) and x then y, the planes layout is
+// the 3d file with nk = 1, giving (ni, nj, 1) and x, y, z for the single k plane.
+// unformatted files are fortran sequential records (each one wrapped in 4 byte length
+// markers) or, without markers, a plain stream. binary values are little endian
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Formatted,
+    Unformatted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    Single,
+    Double,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Whole,
+    Planes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Plot3DFormat {
+    encoding: Encoding,
+    precision: Precision,
+    record_markers: bool,
+    layout: Layout,
+    multi_block: bool,
+}
+
+impl Plot3DFormat {
+    // text file, whole 2d layout, multi-block
+    pub fn formatted() -> Self {
+        Plot3DFormat {
+            encoding: Encoding::Formatted,
+            precision: Precision::Double,
+            record_markers: false,
+            layout: Layout::Whole,
+            multi_block: true,
+        }
+    }
+
+    // fortran unformatted file with record markers, whole 2d layout, multi-block
+    pub fn unformatted(precision: Precision) -> Self {
+        Plot3DFormat {
+            encoding: Encoding::Unformatted,
+            precision,
+            record_markers: true,
+            layout: Layout::Whole,
+            multi_block: true,
+        }
+    }
+
+    // size of binary values, or how many figures a formatted file is written with
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.precision = precision;
+    }
+
+    // only used by unformatted files
+    pub fn set_record_markers(&mut self, record_markers: bool) {
+        self.record_markers = record_markers;
+    }
+
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+
+    // single block files leave out the block count
+    pub fn set_multi_block(&mut self, multi_block: bool) {
+        self.multi_block = multi_block;
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    pub fn record_markers(&self) -> bool {
+        self.record_markers
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn multi_block(&self) -> bool {
+        self.multi_block
+    }
+
+    // coordinates stored per point, z is written as zero in the planes layout
+    fn n_coords(&self) -> usize {
+        match self.layout {
+            Layout::Whole => 2,
+            Layout::Planes => 3,
+        }
+    }
+}
+
+pub fn write(writer: &mut impl Write, blocks: &[Grid2D], format: &Plot3DFormat) -> Result<(), &'static str> {
+    if blocks.is_empty() {
+        return Err("no blocks to write");
+    }
+    if !format.multi_block && blocks.len() > 1 {
+        return Err("single block plot3d file cannot hold more than one block");
+    }
+    if blocks.iter().any(|block| !block.is_complete()) {
+        return Err("every block must have all of its points set");
+    }
+
+    let mut header: Vec<Vec<i32>> = Vec::new();
+    if format.multi_block {
+        header.push(vec![blocks.len() as i32]);
+    }
+    let dims: Vec<i32> = blocks
+        .iter()
+        .flat_map(|block| {
+            let mut dims = vec![block.nx() as i32, block.ny() as i32];
+            if format.layout == Layout::Planes {
+                dims.push(1);
+            }
+            dims
+        })
+        .collect();
+    header.push(dims);
+
+    let coords: Vec<Vec<f64>> = blocks
+        .iter()
+        .map(|block| {
+            let mut values: Vec<f64> = block.points().iter().map(|p| p.x()).collect();
+            values.extend(block.points().iter().map(|p| p.y()));
+            if format.layout == Layout::Planes {
+                values.extend(std::iter::repeat_n(0.0, block.num_pts()));
+            }
+            values
+        })
+        .collect();
+
+    match format.encoding {
+        Encoding::Formatted => write_formatted(writer, &header, &coords, format.precision),
+        Encoding::Unformatted => write_unformatted(writer, &header, &coords, format),
+    }
+    .map_err(|_| "failed to write plot3d file")
+}
+
+pub fn write_file(path: impl AsRef<Path>, blocks: &[Grid2D], format: &Plot3DFormat) -> Result<(), &'static str> {
+    let mut writer = super::create(path)?;
+    write(&mut writer, blocks, format)?;
+    writer.flush().map_err(|_| "failed to write plot3d file")
+}
+
+pub fn read(reader: &mut impl Read, format: &Plot3DFormat) -> Result<Vec<Grid2D>, &'static str> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).map_err(|_| "failed to read plot3d file")?;
+    match format.encoding {
+        Encoding::Formatted => {
+            let text = String::from_utf8(bytes).map_err(|_| "formatted plot3d file is not text")?;
+            read_blocks(&mut TextValues::new(&text), format)
+        }
+        Encoding::Unformatted => read_blocks(&mut BinaryValues::new(bytes, format), format),
+    }
+}
+
+pub fn read_file(path: impl AsRef<Path>, format: &Plot3DFormat) -> Result<Vec<Grid2D>, &'static str> {
+    read(&mut super::open(path)?, format)
+}
+
+fn write_formatted(writer: &mut impl Write, header: &[Vec<i32>], coords: &[Vec<f64>], precision: Precision) -> std::io::Result<()> {
+    for line in header {
+        let line: Vec<String> = line.iter().map(|v| v.to_string()).collect();
+        writeln!(writer, "{}", line.join(" "))?;
+    }
+    for values in coords {
+        for chunk in values.chunks(4) {
+            let line: Vec<String> = chunk
+                .iter()
+                .map(|v| match precision {
+                    Precision::Single => format!("{:15.7e}", *v as f32),
+                    Precision::Double => format!("{:23.15e}", v),
+                })
+                .collect();
+            writeln!(writer, "{}", line.join(" "))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_unformatted(writer: &mut impl Write, header: &[Vec<i32>], coords: &[Vec<f64>], format: &Plot3DFormat) -> std::io::Result<()> {
+    let mut records: Vec<Vec<u8>> = header
+        .iter()
+        .map(|line| line.iter().flat_map(|v| v.to_le_bytes()).collect())
+        .collect();
+    for values in coords {
+        let record: Vec<u8> = match format.precision {
+            Precision::Single => values.iter().flat_map(|&v| (v as f32).to_le_bytes()).collect(),
+            Precision::Double => values.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        };
+        records.push(record);
+    }
+
+    for record in records {
+        let marker = (record.len() as u32).to_le_bytes();
+        if format.record_markers {
+            writer.write_all(&marker)?;
+        }
+        writer.write_all(&record)?;
+        if format.record_markers {
+            writer.write_all(&marker)?;
+        }
+    }
+    Ok(())
+}
+
+// a source of plot3d values, grouped into records for unformatted files
+trait Values {
+    fn begin_record(&mut self) -> Result<(), &'static str>;
+    fn end_record(&mut self) -> Result<(), &'static str>;
+    fn int(&mut self) -> Result<i32, &'static str>;
+    fn float(&mut self) -> Result<f64, &'static str>;
+    // most floats the rest of the file could hold
+    fn remaining(&self) -> usize;
+}
+
+fn read_blocks(values: &mut impl Values, format: &Plot3DFormat) -> Result<Vec<Grid2D>, &'static str> {
+    let n_blocks = if format.multi_block {
+        values.begin_record()?;
+        let n = values.int()?;
+        values.end_record()?;
+        n
+    } else {
+        1
+    };
+    if n_blocks < 1 {
+        return Err("plot3d file must contain at least one block");
+    }
+
+    // sizes in the header are not trusted until the file is shown to hold that much data
+    values.begin_record()?;
+    let mut dims = Vec::new();
+    for _ in 0..n_blocks {
+        let (ni, nj) = (values.int()?, values.int()?);
+        if format.layout == Layout::Planes && values.int()? != 1 {
+            return Err("plot3d block has more than one k plane");
+        }
+        if ni < 1 || nj < 1 {
+            return Err("plot3d block dimensions must be positive");
+        }
+        dims.push((ni as usize, nj as usize));
+    }
+    values.end_record()?;
+
+    let mut blocks = Vec::with_capacity(dims.len());
+    for (ni, nj) in dims {
+        let n = ni
+            .checked_mul(nj)
+            .and_then(|n| n.checked_mul(format.n_coords()))
+            .ok_or("plot3d block dimensions are too large")?;
+        if n > values.remaining() {
+            return Err("plot3d file ended early");
+        }
+        values.begin_record()?;
+        let mut coords = vec![0.0; n];
+        for value in coords.iter_mut() {
+            *value = values.float()?;
+        }
+        values.end_record()?;
+
+        let (x, y) = (&coords[..ni * nj], &coords[ni * nj..2 * ni * nj]);
+        let mut grid = Grid2D::new(ni, nj);
+        for (&x, &y) in x.iter().zip(y) {
+            grid.add_point(x, y);
+        }
+        blocks.push(grid);
+    }
+    Ok(blocks)
+}
+
+// whitespace separated values, records are ignored. fortran writes d exponents sometimes
+struct TextValues<'a> {
+    rest: &'a str,
+}
+
+impl<'a> TextValues<'a> {
+    fn new(text: &'a str) -> Self {
+        TextValues { rest: text }
+    }
+
+    fn token(&mut self) -> Result<&'a str, &'static str> {
+        let rest = self.rest.trim_start();
+        if rest.is_empty() {
+            return Err("plot3d file ended early");
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        self.rest = &rest[end..];
+        Ok(&rest[..end])
+    }
+}
+
+impl Values for TextValues<'_> {
+    fn begin_record(&mut self) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn end_record(&mut self) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn int(&mut self) -> Result<i32, &'static str> {
+        self.token()?.parse().map_err(|_| "expected an integer in plot3d file")
+    }
+
+    fn float(&mut self) -> Result<f64, &'static str> {
+        self.token()?
+            .replace(['D', 'd'], "e")
+            .parse()
+            .map_err(|_| "expected a number in plot3d file")
+    }
+
+    // every value takes at least one character and a separator
+    fn remaining(&self) -> usize {
+        self.rest.len().div_ceil(2)
+    }
+}
+
+// little endian binary values, with each record's length markers checked if there are any
+struct BinaryValues {
+    bytes: Vec<u8>,
+    position: usize,
+    record: Option<(usize, usize)>, // (end, length) of the record being read
+    record_markers: bool,
+    precision: Precision,
+}
+
+impl BinaryValues {
+    fn new(bytes: Vec<u8>, format: &Plot3DFormat) -> Self {
+        BinaryValues {
+            bytes,
+            position: 0,
+            record: None,
+            record_markers: format.record_markers,
+            precision: format.precision,
+        }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], &'static str> {
+        let end = self.position + N;
+        if end > self.bytes.len() || self.record.is_some_and(|(record_end, _)| end > record_end) {
+            return Err("plot3d record ended early");
+        }
+        let mut buffer = [0; N];
+        buffer.copy_from_slice(&self.bytes[self.position..end]);
+        self.position = end;
+        Ok(buffer)
+    }
+
+    fn marker(&mut self) -> Result<usize, &'static str> {
+        Ok(u32::from_le_bytes(self.take::<4>()?) as usize)
+    }
+}
+
+impl Values for BinaryValues {
+    fn begin_record(&mut self) -> Result<(), &'static str> {
+        if self.record_markers {
+            let length = self.marker()?;
+            self.record = Some((self.position + length, length));
+        }
+        Ok(())
+    }
+
+    fn end_record(&mut self) -> Result<(), &'static str> {
+        if let Some((record_end, length)) = self.record.take() {
+            if self.position != record_end {
+                return Err("plot3d record is longer than expected");
+            }
+            if self.marker()? != length {
+                return Err("plot3d record markers do not match");
+            }
+        }
+        Ok(())
+    }
+
+    fn int(&mut self) -> Result<i32, &'static str> {
+        Ok(i32::from_le_bytes(self.take::<4>()?))
+    }
+
+    fn float(&mut self) -> Result<f64, &'static str> {
+        match self.precision {
+            Precision::Single => Ok(f32::from_le_bytes(self.take::<4>()?) as f64),
+            Precision::Double => Ok(f64::from_le_bytes(self.take::<8>()?)),
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        let size = match self.precision {
+            Precision::Single => 4,
+            Precision::Double => 8,
+        };
+        (self.bytes.len() - self.position) / size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(nx: usize, ny: usize, x0: f64) -> Grid2D {
+        let mut grid = Grid2D::new(nx, ny);
+        for j in 0..ny {
+            for i in 0..nx {
+                let (x, y) = (x0 + 0.1 * i as f64, 0.3 * j as f64 + 0.01 * (i * j) as f64);
+                grid.add_point(x, y);
+            }
+        }
+        grid
+    }
+
+    fn assert_same(a: &[Grid2D], b: &[Grid2D], tol: f64) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b) {
+            assert_eq!((a.nx(), a.ny()), (b.nx(), b.ny()));
+            for (p, q) in a.points().iter().zip(b.points()) {
+                assert!((p.x() - q.x()).abs() <= tol * p.x().abs().max(1.0));
+                assert!((p.y() - q.y()).abs() <= tol * p.y().abs().max(1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_every_format() {
+        let blocks = [block(4, 3, 0.0), block(2, 5, 1.0 / 3.0)];
+        for encoding in [Encoding::Formatted, Encoding::Unformatted] {
+            for precision in [Precision::Single, Precision::Double] {
+                for record_markers in [false, true] {
+                    for layout in [Layout::Whole, Layout::Planes] {
+                        for multi_block in [false, true] {
+                            let mut format = match encoding {
+                                Encoding::Formatted => Plot3DFormat::formatted(),
+                                Encoding::Unformatted => Plot3DFormat::unformatted(precision),
+                            };
+                            format.set_precision(precision);
+                            format.set_record_markers(record_markers);
+                            format.set_layout(layout);
+                            format.set_multi_block(multi_block);
+                            let blocks = if multi_block { &blocks[..] } else { &blocks[..1] };
+
+                            let mut bytes = Vec::new();
+                            write(&mut bytes, blocks, &format).unwrap();
+                            let read_back = read(&mut bytes.as_slice(), &format).unwrap();
+                            let tol = if precision == Precision::Single { 1e-7 } else { 1e-15 };
+                            assert_same(blocks, &read_back, tol);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unformatted_record_layout() {
+        let mut bytes = Vec::new();
+        write(&mut bytes, &[block(2, 2, 0.0)], &Plot3DFormat::unformatted(Precision::Double)).unwrap();
+
+        let int = |k: usize| i32::from_le_bytes(bytes[4 * k..4 * k + 4].try_into().unwrap());
+        // [4 | 1 | 4] [8 | 2 2 | 8] [64 | x x x x y y y y | 64]
+        assert_eq!((int(0), int(1), int(2)), (4, 1, 4));
+        assert_eq!((int(3), int(4), int(5), int(6)), (8, 2, 2, 8));
+        assert_eq!(int(7), 64);
+        assert_eq!(bytes.len(), 12 + 16 + 72);
+        assert_eq!(f64::from_le_bytes(bytes[40..48].try_into().unwrap()), 0.1);
+
+        // a damaged trailing marker is caught
+        let last = bytes.len() - 1;
+        bytes[last] = 1;
+        assert!(read(&mut bytes.as_slice(), &Plot3DFormat::unformatted(Precision::Double)).is_err());
+    }
+
+    #[test]
+    fn test_reads_hand_written_file() {
+        // single block planes file with fortran d exponents
+        let text = "3 2 1\n0.0 1.0 2.0\n0.0 1.0 2.0\n0.0D0 0.0 0.0 1.0d0 1.0 1.5\n0 0 0 0 0 0\n";
+        let mut format = Plot3DFormat::formatted();
+        format.set_multi_block(false);
+        format.set_layout(Layout::Planes);
+        let blocks = read(&mut text.as_bytes(), &format).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!((blocks[0].nx(), blocks[0].ny()), (3, 2));
+        assert_eq!((blocks[0].point(2, 1).x(), blocks[0].point(2, 1).y()), (2.0, 1.5));
+    }
+
+    #[test]
+    fn test_rejects_bad_files() {
+        let mut format = Plot3DFormat::formatted();
+        // truncated coordinates, and a 3d grid with more than one plane
+        assert!(read(&mut "1\n2 2\n0.0 1.0 0.0".as_bytes(), &format).is_err());
+        format.set_layout(Layout::Planes);
+        assert!(read(&mut "1\n2 2 2\n".as_bytes(), &format).is_err());
+
+        // headers asking for more data than the file holds, or more than fits in memory
+        let text = Plot3DFormat::formatted();
+        assert_eq!(read(&mut "1\n100000 100000\n0.0".as_bytes(), &text).unwrap_err(), "plot3d file ended early");
+        assert!(read(&mut "2147483647\n".as_bytes(), &text).is_err());
+        let mut binary = Plot3DFormat::unformatted(Precision::Double);
+        binary.set_record_markers(false);
+        binary.set_multi_block(false);
+        let header: Vec<u8> = [i32::MAX, i32::MAX].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(read(&mut header.as_slice(), &binary).unwrap_err(), "plot3d file ended early");
+
+        // two blocks cannot go in a single block file
+        format.set_multi_block(false);
+        let mut bytes = Vec::new();
+        assert!(write(&mut bytes, &[block(2, 2, 0.0), block(2, 2, 1.0)], &format).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::grid::Grid2D;
+use crate::mesh::{Element, Mesh2D};
+use crate::multiblock::MultiBlockGrid2D;
+
+// su2 stuff //
+// native ascii .su2 meshes: the elements with their vtk type numbers, the points, then one
+// marker per named boundary patch made of line elements. indices count from zero
+
+// vtk cell types used by su2
+const LINE: usize = 3;
+const TRIANGLE: usize = 5;
+const QUAD: usize = 9;
+
+pub fn write(writer: &mut impl Write, mesh: &Mesh2D) -> Result<(), &'static str> {
+    su2(writer, mesh).map_err(|_| "failed to write su2 file")
+}
+
+pub fn write_grid(writer: &mut impl Write, grid: &Grid2D) -> Result<(), &'static str> {
+    write(writer, &Mesh2D::from_grid(grid)?)
+}
+
+pub fn write_multiblock(writer: &mut impl Write, grid: &MultiBlockGrid2D) -> Result<(), &'static str> {
+    write(writer, &Mesh2D::from_multiblock(grid)?)
+}
+
+pub fn write_file(path: impl AsRef<Path>, mesh: &Mesh2D) -> Result<(), &'static str> {
+    let mut writer = super::create(path)?;
+    write(&mut writer, mesh)?;
+    writer.flush().map_err(|_| "failed to write su2 file")
+}
+
+fn su2(writer: &mut impl Write, mesh: &Mesh2D) -> std::io::Result<()> {
+    writeln!(writer, "NDIME= 2")?;
+
+    writeln!(writer, "NELEM= {}", mesh.num_elements())?;
+    for (k, element) in mesh.elements().iter().enumerate() {
+        let kind = match element {
+            Element::Triangle(_) => TRIANGLE,
+            Element::Quad(_) => QUAD,
+        };
+        let nodes: Vec<String> = element.nodes().iter().map(|n| n.to_string()).collect();
+        writeln!(writer, "{} {} {}", kind, nodes.join(" "), k)?;
+    }
+
+    writeln!(writer, "NPOIN= {}", mesh.num_nodes())?;
+    for (k, p) in mesh.nodes().iter().enumerate() {
+        writeln!(writer, "{:e} {:e} {}", p.x(), p.y(), k)?;
+    }
+
+    writeln!(writer, "NMARK= {}", mesh.patches().len())?;
+    for patch in mesh.patches() {
+        // marker names are listed in the su2 config, so keep them to one word
+        writeln!(writer, "MARKER_TAG= {}", patch.name().split_whitespace().collect::<Vec<&str>>().join("_"))?;
+        writeln!(writer, "MARKER_ELEMS= {}", patch.edges().len())?;
+        for [a, b] in patch.edges() {
+            writeln!(writer, "{} {} {}", LINE, a, b)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Side;
+
+    #[test]
+    fn test_writes_grid_with_markers() {
+        let mut grid = Grid2D::new(3, 2);
+        for j in 0..2 {
+            for i in 0..3 {
+                grid.add_point(0.5 * i as f64, j as f64);
+            }
+        }
+        grid.add_patch("lower wall", Side::Bottom, 0..=2).unwrap();
+        grid.add_patch("inlet", Side::Left, 0..=1).unwrap();
+
+        let mut bytes = Vec::new();
+        write_grid(&mut bytes, &grid).unwrap();
+        let expected = "\
+NDIME= 2
+NELEM= 2
+9 0 1 4 3 0
+9 1 2 5 4 1
+NPOIN= 6
+0e0 0e0 0
+5e-1 0e0 1
+1e0 0e0 2
+0e0 1e0 3
+5e-1 1e0 4
+1e0 1e0 5
+NMARK= 2
+MARKER_TAG= lower_wall
+MARKER_ELEMS= 2
+3 0 1
+3 1 2
+MARKER_TAG= inlet
+MARKER_ELEMS= 1
+3 0 3
+";
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_left_handed_grid_gives_anticlockwise_quads() {
+        let mut grid = Grid2D::new(2, 2);
+        for (x, y) in [(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)] {
+            grid.add_point(x, y);
+        }
+        let mut bytes = Vec::new();
+        write_grid(&mut bytes, &grid).unwrap();
+        assert!(String::from_utf8(bytes).unwrap().contains("NELEM= 1\n9 2 3 1 0 0\n"));
+    }
+}
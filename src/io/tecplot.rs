@@ -0,0 +1,174 @@
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::grid::{FieldData, FieldLocation, Grid2D};
+use crate::multiblock::MultiBlockGrid2D;
+
+// tecplot stuff //
+// ascii .dat files with one ordered zone per block, written in block data packing: every
+// value of one variable, then the next. x and y come first, then the fields attached to the
+// blocks, 2d vectors split into _x and _y parts. cell fields are cell centred, and a field
+// missing from some block is passive in that block's zone
+
+// values written per line
+const VALUES_PER_LINE: usize = 5;
+
+pub fn write(writer: &mut impl Write, blocks: &[Grid2D], title: &str) -> Result<(), &'static str> {
+    if blocks.is_empty() {
+        return Err("no blocks to write");
+    }
+    if blocks.iter().any(|block| !block.is_complete()) {
+        return Err("every block must have all of its points set");
+    }
+    dat(writer, blocks, title).map_err(|_| "failed to write tecplot file")
+}
+
+pub fn write_multiblock(writer: &mut impl Write, grid: &MultiBlockGrid2D, title: &str) -> Result<(), &'static str> {
+    write(writer, grid.blocks(), title)
+}
+
+pub fn write_file(path: impl AsRef<Path>, blocks: &[Grid2D], title: &str) -> Result<(), &'static str> {
+    let mut writer = super::create(path)?;
+    write(&mut writer, blocks, title)?;
+    writer.flush().map_err(|_| "failed to write tecplot file")
+}
+
+// a column of values in one zone, with the location it is given at
+struct Variable {
+    location: FieldLocation,
+    values: Vec<f64>,
+}
+
+// the variables a block provides, by name
+fn variables(block: &Grid2D) -> Vec<(String, Variable)> {
+    let node = |values: Vec<f64>| Variable { location: FieldLocation::Node, values };
+    let mut variables = vec![
+        ("X".to_string(), node(block.points().iter().map(|p| p.x()).collect())),
+        ("Y".to_string(), node(block.points().iter().map(|p| p.y()).collect())),
+    ];
+    for field in block.fields() {
+        let location = field.location();
+        match field.data() {
+            FieldData::Scalar(values) => variables.push((field.name().to_string(), Variable { location, values: values.clone() })),
+            FieldData::Vector(values) => {
+                for (suffix, d) in [("x", 0), ("y", 1)] {
+                    let values = values.iter().map(|v| v[d]).collect();
+                    variables.push((format!("{}_{}", field.name(), suffix), Variable { location, values }));
+                }
+            }
+        }
+    }
+    variables
+}
+
+fn dat(writer: &mut impl Write, blocks: &[Grid2D], title: &str) -> std::io::Result<()> {
+    let zones: Vec<Vec<(String, Variable)>> = blocks.iter().map(variables).collect();
+
+    // every zone shares one variable list, in the order names are first met
+    let mut names: Vec<&str> = Vec::new();
+    for zone in zones.iter() {
+        for (name, _) in zone {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+    }
+
+    writeln!(writer, "TITLE = \"{}\"", quote(title))?;
+    let quoted: Vec<String> = names.iter().map(|name| format!("\"{}\"", quote(name))).collect();
+    writeln!(writer, "VARIABLES = {}", quoted.join(", "))?;
+
+    for (k, (block, zone)) in blocks.iter().zip(zones.iter()).enumerate() {
+        let mut cell_centred = Vec::new();
+        let mut passive = Vec::new();
+        for (v, name) in names.iter().enumerate() {
+            match zone.iter().find(|(n, _)| n == name) {
+                Some((_, variable)) if variable.location == FieldLocation::Cell => cell_centred.push(v + 1),
+                Some(_) => {}
+                None => passive.push(v + 1),
+            }
+        }
+
+        write!(writer, "ZONE T=\"block {}\", I={}, J={}, DATAPACKING=BLOCK", k + 1, block.nx(), block.ny())?;
+        if !cell_centred.is_empty() {
+            write!(writer, ", VARLOCATION=({}=CELLCENTERED)", list(&cell_centred))?;
+        }
+        if !passive.is_empty() {
+            write!(writer, ", PASSIVEVARLIST={}", list(&passive))?;
+        }
+        writeln!(writer)?;
+
+        for name in names.iter() {
+            if let Some((_, variable)) = zone.iter().find(|(n, _)| n == name) {
+                for chunk in variable.values.chunks(VALUES_PER_LINE) {
+                    let line: Vec<String> = chunk.iter().map(|v| format!("{:e}", v)).collect();
+                    writeln!(writer, "{}", line.join(" "))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// 1 based variable numbers as a tecplot list, e.g. [3,5]
+fn list(numbers: &[usize]) -> String {
+    let numbers: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+    format!("[{}]", numbers.join(","))
+}
+
+fn quote(text: &str) -> String {
+    text.replace('"', "'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(x0: f64) -> Grid2D {
+        let mut grid = Grid2D::new(3, 2);
+        for j in 0..2 {
+            for i in 0..3 {
+                grid.add_point(x0 + i as f64, j as f64);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_grid_only() {
+        let mut bytes = Vec::new();
+        write(&mut bytes, &[block(0.0)], "grid").unwrap();
+        let expected = "\
+TITLE = \"grid\"
+VARIABLES = \"X\", \"Y\"
+ZONE T=\"block 1\", I=3, J=2, DATAPACKING=BLOCK
+0e0 1e0 2e0 0e0 1e0
+2e0
+0e0 0e0 0e0 1e0 1e0
+1e0
+";
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_node_and_cell_variables_across_zones() {
+        let mut first = block(0.0);
+        first.add_field("jacobian", FieldLocation::Cell, FieldData::Scalar(vec![1.0, 2.0])).unwrap();
+        let mut second = block(2.0);
+        second.add_field("normal", FieldLocation::Node, FieldData::Vector(vec![[0.0, 1.0]; 6])).unwrap();
+        second.add_field("jacobian", FieldLocation::Cell, FieldData::Scalar(vec![3.0, 4.0])).unwrap();
+
+        let mut bytes = Vec::new();
+        write(&mut bytes, &[first, second], "two blocks").unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[1], "VARIABLES = \"X\", \"Y\", \"jacobian\", \"normal_x\", \"normal_y\"");
+        assert_eq!(lines[2], "ZONE T=\"block 1\", I=3, J=2, DATAPACKING=BLOCK, VARLOCATION=([3]=CELLCENTERED), PASSIVEVARLIST=[4,5]");
+        assert_eq!(lines[7], "1e0 2e0");
+        assert_eq!(lines[8], "ZONE T=\"block 2\", I=3, J=2, DATAPACKING=BLOCK, VARLOCATION=([3]=CELLCENTERED)");
+        assert_eq!(lines[13..], ["3e0 4e0", "0e0 0e0 0e0 0e0 0e0", "0e0", "1e0 1e0 1e0 1e0 1e0", "1e0"]);
+    }
+}
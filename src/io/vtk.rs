@@ -0,0 +1,228 @@
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::grid::{Field, FieldData, FieldLocation, Grid2D};
+
+// vtk stuff //
+// structured grid output for paraview, either the legacy ascii .vtk file or the xml .vts
+// file with its data arrays appended as raw little endian binary after the xml. the grid is
+// written as a single k plane with z = 0, and any fields attached to it are written as point
+// or cell data, 2d vectors getting a zero z component
+
+pub fn write_legacy(writer: &mut impl Write, grid: &Grid2D, title: &str) -> Result<(), &'static str> {
+    check(grid)?;
+    legacy(writer, grid, title).map_err(|_| "failed to write vtk file")
+}
+
+pub fn write_legacy_file(path: impl AsRef<Path>, grid: &Grid2D, title: &str) -> Result<(), &'static str> {
+    let mut writer = super::create(path)?;
+    write_legacy(&mut writer, grid, title)?;
+    writer.flush().map_err(|_| "failed to write vtk file")
+}
+
+pub fn write_xml(writer: &mut impl Write, grid: &Grid2D) -> Result<(), &'static str> {
+    check(grid)?;
+    xml(writer, grid).map_err(|_| "failed to write vts file")
+}
+
+pub fn write_xml_file(path: impl AsRef<Path>, grid: &Grid2D) -> Result<(), &'static str> {
+    let mut writer = super::create(path)?;
+    write_xml(&mut writer, grid)?;
+    writer.flush().map_err(|_| "failed to write vts file")
+}
+
+fn check(grid: &Grid2D) -> Result<(), &'static str> {
+    if !grid.is_complete() {
+        return Err("grid must have every point set");
+    }
+    Ok(())
+}
+
+fn fields_at(grid: &Grid2D, location: FieldLocation) -> impl Iterator<Item = &Field> {
+    grid.fields().iter().filter(move |field| field.location() == location)
+}
+
+fn legacy(writer: &mut impl Write, grid: &Grid2D, title: &str) -> std::io::Result<()> {
+    // the title is a single line of at most 256 characters
+    let title: String = title.lines().next().unwrap_or("").chars().take(255).collect();
+    writeln!(writer, "# vtk DataFile Version 3.0")?;
+    writeln!(writer, "{}", if title.is_empty() { "grid" } else { &title })?;
+    writeln!(writer, "ASCII")?;
+    writeln!(writer, "DATASET STRUCTURED_GRID")?;
+    writeln!(writer, "DIMENSIONS {} {} 1", grid.nx(), grid.ny())?;
+    writeln!(writer, "POINTS {} double", grid.num_pts())?;
+    for point in grid.points() {
+        writeln!(writer, "{:e} {:e} 0", point.x(), point.y())?;
+    }
+
+    for (location, keyword, count) in [
+        (FieldLocation::Node, "POINT_DATA", grid.num_pts()),
+        (FieldLocation::Cell, "CELL_DATA", grid.num_cells()),
+    ] {
+        let mut fields = fields_at(grid, location).peekable();
+        if fields.peek().is_none() {
+            continue;
+        }
+        writeln!(writer, "{} {}", keyword, count)?;
+        for field in fields {
+            // legacy names are single tokens
+            let name: String = field.name().split_whitespace().collect::<Vec<&str>>().join("_");
+            match field.data() {
+                FieldData::Scalar(values) => {
+                    writeln!(writer, "SCALARS {} double 1", name)?;
+                    writeln!(writer, "LOOKUP_TABLE default")?;
+                    for value in values {
+                        writeln!(writer, "{:e}", value)?;
+                    }
+                }
+                FieldData::Vector(values) => {
+                    writeln!(writer, "VECTORS {} double", name)?;
+                    for value in values {
+                        writeln!(writer, "{:e} {:e} 0", value[0], value[1])?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn xml(writer: &mut impl Write, grid: &Grid2D) -> std::io::Result<()> {
+    let extent = format!("0 {} 0 {} 0 0", grid.nx() - 1, grid.ny() - 1);
+    writeln!(writer, "<?xml version=\"1.0\"?>")?;
+    writeln!(writer, "<VTKFile type=\"StructuredGrid\" version=\"1.0\" byte_order=\"LittleEndian\" header_type=\"UInt64\">")?;
+    writeln!(writer, "  <StructuredGrid WholeExtent=\"{}\">", extent)?;
+    writeln!(writer, "    <Piece Extent=\"{}\">", extent)?;
+
+    // every array goes in the appended block, each one a byte count then the values
+    let mut appended: Vec<u8> = Vec::new();
+    let mut array = |writer: &mut dyn Write, name: Option<&str>, components: usize, values: Vec<f64>| -> std::io::Result<()> {
+        let name = match name {
+            Some(name) => format!(" Name=\"{}\"", escape(name)),
+            None => String::new(),
+        };
+        writeln!(
+            writer,
+            "        <DataArray type=\"Float64\"{} NumberOfComponents=\"{}\" format=\"appended\" offset=\"{}\"/>",
+            name,
+            components,
+            appended.len()
+        )?;
+        appended.extend(((values.len() * 8) as u64).to_le_bytes());
+        appended.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+        Ok(())
+    };
+
+    for (location, tag) in [(FieldLocation::Node, "PointData"), (FieldLocation::Cell, "CellData")] {
+        writeln!(writer, "      <{}>", tag)?;
+        for field in fields_at(grid, location) {
+            match field.data() {
+                FieldData::Scalar(values) => array(writer, Some(field.name()), 1, values.clone())?,
+                FieldData::Vector(values) => {
+                    let values = values.iter().flat_map(|v| [v[0], v[1], 0.0]).collect();
+                    array(writer, Some(field.name()), 3, values)?
+                }
+            }
+        }
+        writeln!(writer, "      </{}>", tag)?;
+    }
+
+    writeln!(writer, "      <Points>")?;
+    let points = grid.points().iter().flat_map(|p| [p.x(), p.y(), 0.0]).collect();
+    array(writer, None, 3, points)?;
+    writeln!(writer, "      </Points>")?;
+    writeln!(writer, "    </Piece>")?;
+    writeln!(writer, "  </StructuredGrid>")?;
+
+    writeln!(writer, "  <AppendedData encoding=\"raw\">")?;
+    write!(writer, "   _")?;
+    writer.write_all(&appended)?;
+    writeln!(writer)?;
+    writeln!(writer, "  </AppendedData>")?;
+    writeln!(writer, "</VTKFile>")?;
+    Ok(())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid2D {
+        let mut grid = Grid2D::new(3, 2);
+        for j in 0..2 {
+            for i in 0..3 {
+                grid.add_point(i as f64, 0.5 * j as f64);
+            }
+        }
+        grid.add_field("jacobian", FieldLocation::Cell, FieldData::Scalar(vec![0.5, 0.25])).unwrap();
+        let velocity = grid.points().iter().map(|p| [p.y(), -p.x()]).collect();
+        grid.add_field("grid velocity", FieldLocation::Node, FieldData::Vector(velocity)).unwrap();
+        grid
+    }
+
+    #[test]
+    fn test_legacy_ascii() {
+        let mut bytes = Vec::new();
+        write_legacy(&mut bytes, &grid(), "test grid").unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], "# vtk DataFile Version 3.0");
+        assert_eq!(lines[1..6], ["test grid", "ASCII", "DATASET STRUCTURED_GRID", "DIMENSIONS 3 2 1", "POINTS 6 double"]);
+        assert_eq!(lines[11], "2e0 5e-1 0");
+        assert_eq!(lines[12..14], ["POINT_DATA 6", "VECTORS grid_velocity double"]);
+        assert_eq!(lines[15], "0e0 -1e0 0");
+        assert_eq!(lines[20..], ["CELL_DATA 2", "SCALARS jacobian double 1", "LOOKUP_TABLE default", "5e-1", "2.5e-1"]);
+    }
+
+    #[test]
+    fn test_xml_appended_binary() {
+        let mut bytes = Vec::new();
+        write_xml(&mut bytes, &grid()).unwrap();
+
+        // split the xml header from the raw data that follows the underscore
+        let marker = b"<AppendedData encoding=\"raw\">\n   _";
+        let start = bytes.windows(marker.len()).position(|w| w == marker).unwrap() + marker.len();
+        let header = String::from_utf8(bytes[..start].to_vec()).unwrap();
+        assert!(header.contains("<StructuredGrid WholeExtent=\"0 2 0 1 0 0\">"));
+        assert!(String::from_utf8_lossy(&bytes[start..]).ends_with("</AppendedData>\n</VTKFile>\n"));
+
+        let offset = |name: &str| -> usize {
+            let tag = header.lines().find(|line| line.contains(name)).unwrap();
+            let value = tag.split("offset=\"").nth(1).unwrap();
+            value[..value.find('"').unwrap()].parse().unwrap()
+        };
+        let array = |offset: usize| -> Vec<f64> {
+            let data = &bytes[start + offset..];
+            let n = u64::from_le_bytes(data[..8].try_into().unwrap()) as usize;
+            data[8..8 + n].chunks(8).map(|c| f64::from_le_bytes(c.try_into().unwrap())).collect()
+        };
+
+        assert_eq!(array(offset("Name=\"jacobian\"")), vec![0.5, 0.25]);
+        let velocity = array(offset("Name=\"grid velocity\""));
+        assert_eq!(velocity.len(), 18);
+        assert_eq!(velocity[15..], [0.5, -2.0, 0.0]);
+        let points = array(offset("<DataArray type=\"Float64\" NumberOfComponents=\"3\""));
+        assert_eq!(points[3..6], [1.0, 0.0, 0.0]);
+        assert_eq!(points[15..], [2.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_field_checks() {
+        let mut grid = grid();
+        assert!(grid.add_field("bad", FieldLocation::Node, FieldData::Scalar(vec![0.0; 2])).is_err());
+        grid.add_field("jacobian", FieldLocation::Node, FieldData::Scalar(vec![1.0; 6])).unwrap();
+        assert_eq!(grid.fields().len(), 2);
+        assert_eq!(grid.field("jacobian").unwrap().location(), FieldLocation::Node);
+        assert!(write_xml(&mut Vec::new(), &Grid2D::new(2, 2)).is_err());
+    }
+}
@@ -1,5 +1,8 @@
 mod grid;
+mod io;
 mod generator;
+mod mesh;
+mod multiblock;
 mod utils;
 mod point;
 
@@ -8,5 +11,3 @@ fn main() {
 }
 
 // main to handle command line
-
-// - mesh.rs to handle mesh data structure
\ No newline at end of file
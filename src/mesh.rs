@@ -0,0 +1,326 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::grid::Grid2D;
+use crate::multiblock::{Connection, MultiBlockGrid2D};
+use crate::utils::geometry::Cartesian2D;
+
+// unstructured mesh stuff //
+// nodes, triangle and quad elements given by node index, and named sets of boundary edges.
+// structured grids convert to this with the points that periodic seams, wake cuts and block
+// connections say are the same merged into one node, which is what the unstructured file
+// formats need
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+    Triangle([usize; 3]),
+    Quad([usize; 4]),
+}
+
+impl Element {
+    pub fn nodes(&self) -> &[usize] {
+        match self {
+            Element::Triangle(nodes) => nodes,
+            Element::Quad(nodes) => nodes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeshPatch {
+    name: String,
+    edges: Vec<[usize; 2]>,
+}
+
+impl MeshPatch {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn edges(&self) -> &[[usize; 2]] {
+        &self.edges
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Mesh2D {
+    nodes: Vec<Cartesian2D>,
+    elements: Vec<Element>,
+    patches: Vec<MeshPatch>,
+}
+
+impl Mesh2D {
+    pub fn new() -> Self {
+        Mesh2D { nodes: Vec::new(), elements: Vec::new(), patches: Vec::new() }
+    }
+
+    // quads for every cell of a grid
+    pub fn from_grid(grid: &Grid2D) -> Result<Self, &'static str> {
+        Self::from_blocks(std::slice::from_ref(grid), &[])
+    }
+
+    // quads for every cell of every block, with connected points shared between blocks
+    pub fn from_multiblock(grid: &MultiBlockGrid2D) -> Result<Self, &'static str> {
+        Self::from_blocks(grid.blocks(), grid.connections())
+    }
+
+    fn from_blocks(blocks: &[Grid2D], connections: &[Connection]) -> Result<Self, &'static str> {
+        if blocks.iter().any(|block| !block.is_complete()) {
+            return Err("every block must have all of its points set");
+        }
+        let numbering = NodeNumbering::new(blocks, connections);
+        let mut mesh = Mesh2D::new();
+        mesh.nodes = numbering.points.clone();
+
+        for (b, block) in blocks.iter().enumerate() {
+            let node = |i: usize, j: usize| numbering.id(b, block, i, j);
+            // elements run anticlockwise whichever way the block's indices go
+            let flip = signed_area(block) < 0.0;
+            for j in 0..block.ny() - 1 {
+                for i in 0..block.nx() - 1 {
+                    let mut quad = [node(i, j), node(i + 1, j), node(i + 1, j + 1), node(i, j + 1)];
+                    if flip {
+                        quad.reverse();
+                    }
+                    mesh.elements.push(Element::Quad(quad));
+                }
+            }
+            for patch in block.patches() {
+                let edges: Vec<[usize; 2]> = patch
+                    .edges(block)
+                    .map(|((i0, j0), (i1, j1))| [node(i0, j0), node(i1, j1)])
+                    .collect();
+                mesh.add_patch(patch.name(), edges)?;
+            }
+        }
+        Ok(mesh)
+    }
+
+    pub fn add_node(&mut self, point: Cartesian2D) -> usize {
+        self.nodes.push(point);
+        self.nodes.len() - 1
+    }
+
+    pub fn add_element(&mut self, element: Element) -> Result<(), &'static str> {
+        if element.nodes().iter().any(|&n| n >= self.nodes.len()) {
+            return Err("element refers to a node that does not exist");
+        }
+        self.elements.push(element);
+        Ok(())
+    }
+
+    // adds boundary edges to the patch with this name, creating it if needed
+    pub fn add_patch(&mut self, name: &str, edges: Vec<[usize; 2]>) -> Result<(), &'static str> {
+        if name.trim().is_empty() {
+            return Err("patch name must not be empty");
+        }
+        if edges.iter().flatten().any(|&n| n >= self.nodes.len()) {
+            return Err("patch edge refers to a node that does not exist");
+        }
+        match self.patches.iter_mut().find(|p| p.name == name) {
+            Some(patch) => patch.edges.extend(edges),
+            None => self.patches.push(MeshPatch { name: name.to_string(), edges }),
+        }
+        Ok(())
+    }
+
+    pub fn nodes(&self) -> &[Cartesian2D] {
+        &self.nodes
+    }
+
+    pub fn elements(&self) -> &[Element] {
+        &self.elements
+    }
+
+    pub fn patches(&self) -> &[MeshPatch] {
+        &self.patches
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn num_elements(&self) -> usize {
+        self.elements.len()
+    }
+}
+
+// one node number per distinct point over a set of blocks. points tied together by a
+// periodic seam, a wake cut or a block connection are merged with a union find
+struct NodeNumbering {
+    offsets: Vec<usize>,
+    ids: Vec<usize>,
+    points: Vec<Cartesian2D>,
+}
+
+impl NodeNumbering {
+    fn new(blocks: &[Grid2D], connections: &[Connection]) -> Self {
+        let mut offsets = Vec::with_capacity(blocks.len());
+        let mut total = 0;
+        for block in blocks {
+            offsets.push(total);
+            total += block.num_pts();
+        }
+        let index = |b: usize, i: usize, j: usize| offsets[b] + j * blocks[b].nx() + i;
+
+        let mut parent: Vec<usize> = (0..total).collect();
+        for (b, block) in blocks.iter().enumerate() {
+            if block.is_periodic() {
+                for j in 0..block.ny() {
+                    union(&mut parent, index(b, 0, j), index(b, block.nx() - 1, j));
+                }
+            }
+            for cut in block.wake_cuts() {
+                for (lower, upper) in cut.pairs() {
+                    union(&mut parent, index(b, lower, 0), index(b, upper, 0));
+                }
+            }
+        }
+        for connection in connections {
+            let (first, second) = (connection.first(), connection.second());
+            let (a, c) = (&blocks[first.block()], &blocks[second.block()]);
+            for (ka, kc) in connection.pairs() {
+                let (ia, ja) = a.side_index(first.side(), ka);
+                let (ic, jc) = c.side_index(second.side(), kc);
+                union(&mut parent, index(first.block(), ia, ja), index(second.block(), ic, jc));
+            }
+        }
+
+        // number the merged points in the order they are first met
+        let mut numbers: HashMap<usize, usize> = HashMap::new();
+        let mut ids = Vec::with_capacity(total);
+        let mut points = Vec::new();
+        for (b, block) in blocks.iter().enumerate() {
+            for point in block.points() {
+                let root = find(&mut parent, index(b, point.i(), point.j()));
+                let id = *numbers.entry(root).or_insert_with(|| {
+                    points.push(Cartesian2D::new(point.x(), point.y()));
+                    points.len() - 1
+                });
+                ids.push(id);
+            }
+        }
+        NodeNumbering { offsets, ids, points }
+    }
+
+    fn id(&self, b: usize, block: &Grid2D, i: usize, j: usize) -> usize {
+        self.ids[self.offsets[b] + j * block.nx() + i]
+    }
+}
+
+fn find(parent: &mut [usize], k: usize) -> usize {
+    let mut root = k;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    // path compression
+    let mut k = k;
+    while parent[k] != root {
+        let next = parent[k];
+        parent[k] = root;
+        k = next;
+    }
+    root
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a != b {
+        parent[a.max(b)] = a.min(b);
+    }
+}
+
+fn signed_area(grid: &Grid2D) -> f64 {
+    let mut area = 0.0;
+    for j in 0..grid.ny() - 1 {
+        for i in 0..grid.nx() - 1 {
+            let (a, b) = (grid.point(i, j), grid.point(i + 1, j));
+            let (c, d) = (grid.point(i + 1, j + 1), grid.point(i, j + 1));
+            area += 0.5 * ((c.x() - a.x()) * (d.y() - b.y()) - (c.y() - a.y()) * (d.x() - b.x()));
+        }
+    }
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::topology::{CGridGenerator, OGridGenerator};
+    use crate::grid::Side;
+    use crate::multiblock::{BlockFace, Orientation};
+
+    fn circle(n: usize) -> Vec<Cartesian2D> {
+        (0..=n)
+            .map(|k| {
+                let theta = 2.0 * std::f64::consts::PI * (k % n) as f64 / n as f64;
+                Cartesian2D::new(theta.cos(), 0.2 * theta.sin())
+            })
+            .collect()
+    }
+
+    fn quad_area(mesh: &Mesh2D, nodes: &[usize]) -> f64 {
+        let p: Vec<&Cartesian2D> = nodes.iter().map(|&n| &mesh.nodes()[n]).collect();
+        0.5 * ((p[2].x() - p[0].x()) * (p[3].y() - p[1].y()) - (p[2].y() - p[0].y()) * (p[3].x() - p[1].x()))
+    }
+
+    #[test]
+    fn test_o_grid_seam_is_merged() {
+        let grid = OGridGenerator::new(circle(16), 5).unwrap().generate().unwrap();
+        let mesh = Mesh2D::from_grid(&grid).unwrap();
+        assert_eq!(mesh.num_nodes(), 16 * 5);
+        assert_eq!(mesh.num_elements(), 16 * 4);
+        assert!(mesh.elements().iter().all(|e| quad_area(&mesh, e.nodes()) > 0.0));
+
+        let wall = &mesh.patches()[0];
+        assert_eq!((wall.name(), wall.edges().len()), ("wall", 16));
+        // the wall closes on itself through the seam
+        assert_eq!(wall.edges()[15][1], wall.edges()[0][0]);
+    }
+
+    #[test]
+    fn test_c_grid_wake_cut_is_merged() {
+        let grid = CGridGenerator::new(circle(20), 6, 5).unwrap().generate().unwrap();
+        let mesh = Mesh2D::from_grid(&grid).unwrap();
+        assert_eq!(mesh.num_nodes(), grid.num_pts() - 6);
+        assert_eq!(mesh.patches().iter().map(|p| p.name()).collect::<Vec<_>>(), ["wall", "farfield", "outflow"]);
+        assert_eq!(mesh.patches()[2].edges().len(), 8);
+    }
+
+    #[test]
+    fn test_blocks_share_connected_nodes() {
+        let block = |x0: f64| {
+            let mut grid = Grid2D::new(3, 3);
+            for j in 0..3 {
+                for i in 0..3 {
+                    grid.add_point(x0 + i as f64, j as f64);
+                }
+            }
+            grid
+        };
+        let mut grid = MultiBlockGrid2D::new();
+        grid.add_block(block(0.0)).unwrap();
+        grid.add_block(block(2.0)).unwrap();
+        grid.add_connection(Connection::new(
+            BlockFace::new(0, Side::Right, 0..=2).unwrap(),
+            BlockFace::new(1, Side::Left, 0..=2).unwrap(),
+            Orientation::Aligned,
+        ).unwrap()).unwrap();
+
+        let mesh = Mesh2D::from_multiblock(&grid).unwrap();
+        assert_eq!(mesh.num_nodes(), 15);
+        assert_eq!(mesh.elements()[4], Element::Quad([2, 9, 11, 5]));
+    }
+
+    #[test]
+    fn test_rejects_bad_elements() {
+        let mut mesh = Mesh2D::new();
+        mesh.add_node(Cartesian2D::new(0.0, 0.0));
+        mesh.add_node(Cartesian2D::new(1.0, 0.0));
+        assert!(mesh.add_element(Element::Triangle([0, 1, 2])).is_err());
+        assert!(mesh.add_patch("wall", vec![[0, 3]]).is_err());
+        mesh.add_patch("wall", vec![[0, 1]]).unwrap();
+        mesh.add_patch("wall", vec![[1, 0]]).unwrap();
+        assert_eq!(mesh.patches().len(), 1);
+        assert_eq!(mesh.patches()[0].edges().len(), 2);
+    }
+}
@@ -0,0 +1,858 @@
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::grid::{Grid2D, Side};
+
+// multi-block stuff //
+// a set of structured blocks plus one-to-one connections between stretches of their sides.
+// each connection says that points along one side of a block coincide, one for one, with
+// points along a side of another block (or another part of the same block)
+
+// a stretch of one side of one block, counted along the side in increasing index order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockFace {
+    block: usize,
+    side: Side,
+    range: RangeInclusive<usize>,
+}
+
+// whether the index along the two faces runs the same way or in opposite directions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Aligned,
+    Reversed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    first: BlockFace,
+    second: BlockFace,
+    orientation: Orientation,
+}
+
+// something odd found while detecting connections, for the user to look at
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceDiagnostic {
+    kind: DiagnosticKind,
+    face: BlockFace,
+    other_block: usize,
+    other_side: Side,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    // part of the interface between two sides matched, but further nodes of the face lie on
+    // the other side without coinciding with its nodes
+    PartialMatch,
+    // nodes of the face lie on the other side but none of them coincide with its nodes,
+    // e.g. the two blocks have different spacing along the interface
+    NonMatching,
+}
+
+#[derive(Debug, Clone)]
+pub struct MultiBlockGrid2D {
+    blocks: Vec<Grid2D>,
+    connections: Vec<Connection>,
+    tolerance: f64,
+}
+
+impl BlockFace {
+    pub fn new(block: usize, side: Side, range: RangeInclusive<usize>) -> Result<Self, &'static str> {
+        if range.is_empty() {
+            return Err("block face range must not be empty");
+        }
+        Ok(BlockFace { block, side, range })
+    }
+
+    pub fn block(&self) -> usize {
+        self.block
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn range(&self) -> &RangeInclusive<usize> {
+        &self.range
+    }
+
+    // number of points on the face
+    pub fn len(&self) -> usize {
+        self.range.end() - self.range.start() + 1
+    }
+
+    fn overlaps(&self, other: &BlockFace) -> bool {
+        self.block == other.block
+            && self.side == other.side
+            && self.range.start() <= other.range.end()
+            && other.range.start() <= self.range.end()
+    }
+}
+
+impl Connection {
+    pub fn new(first: BlockFace, second: BlockFace, orientation: Orientation) -> Result<Self, &'static str> {
+        if first.len() != second.len() {
+            return Err("connected faces must have the same number of points");
+        }
+        if first.len() < 2 {
+            return Err("connected faces must have at least two points");
+        }
+        if first.overlaps(&second) {
+            return Err("a face cannot be connected to itself");
+        }
+        Ok(Connection { first, second, orientation })
+    }
+
+    pub fn first(&self) -> &BlockFace {
+        &self.first
+    }
+
+    pub fn second(&self) -> &BlockFace {
+        &self.second
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    // matching (first, second) positions along the two sides
+    pub fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let second: Box<dyn Iterator<Item = usize>> = match self.orientation {
+            Orientation::Aligned => Box::new(self.second.range.clone()),
+            Orientation::Reversed => Box::new(self.second.range.clone().rev()),
+        };
+        self.first.range.clone().zip(second)
+    }
+}
+
+impl InterfaceDiagnostic {
+    pub fn kind(&self) -> DiagnosticKind {
+        self.kind
+    }
+
+    // the stretch of face whose nodes do not match
+    pub fn face(&self) -> &BlockFace {
+        &self.face
+    }
+
+    pub fn other_block(&self) -> usize {
+        self.other_block
+    }
+
+    pub fn other_side(&self) -> Side {
+        self.other_side
+    }
+}
+
+impl fmt::Display for InterfaceDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            DiagnosticKind::PartialMatch => "partially matching",
+            DiagnosticKind::NonMatching => "non-matching",
+        };
+        write!(
+            f,
+            "{} interface: block {} {:?} points {}..={} lie on block {} {:?} without coinciding with its nodes",
+            what,
+            self.face.block,
+            self.face.side,
+            self.face.range.start(),
+            self.face.range.end(),
+            self.other_block,
+            self.other_side,
+        )
+    }
+}
+
+impl MultiBlockGrid2D {
+    // default relative tolerance for connected points to coincide, as a fraction of the
+    // size of the two blocks involved
+    const CONNECTION_TOL: f64 = 1e-10;
+
+    pub fn new() -> Self {
+        MultiBlockGrid2D { blocks: Vec::new(), connections: Vec::new(), tolerance: Self::CONNECTION_TOL }
+    }
+
+    // relative tolerance used when checking connections, set it before adding them
+    pub fn set_tolerance(&mut self, tolerance: f64) -> Result<(), &'static str> {
+        if tolerance <= 0.0 {
+            return Err("tolerance must be positive");
+        }
+        self.tolerance = tolerance;
+        Ok(())
+    }
+
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    // adds a filled block, returning its index
+    pub fn add_block(&mut self, grid: Grid2D) -> Result<usize, &'static str> {
+        if !grid.is_complete() {
+            return Err("block must have every point set");
+        }
+        self.blocks.push(grid);
+        Ok(self.blocks.len() - 1)
+    }
+
+    // records a connection, checking the faces exist and their points coincide
+    pub fn add_connection(&mut self, connection: Connection) -> Result<(), &'static str> {
+        self.check_connection(&connection)?;
+        self.connections.push(connection);
+        Ok(())
+    }
+
+    fn check_connection(&self, connection: &Connection) -> Result<(), &'static str> {
+        for face in [&connection.first, &connection.second] {
+            let block = self.blocks.get(face.block).ok_or("connection refers to a block that does not exist")?;
+            if *face.range.end() >= block.side_len(face.side) {
+                return Err("connection range is outside the block side");
+            }
+        }
+
+        let (a, b) = (&self.blocks[connection.first.block], &self.blocks[connection.second.block]);
+        let tol = self.tolerance * diagonal(a).max(diagonal(b));
+        for (ka, kb) in connection.pairs() {
+            let (ia, ja) = a.side_index(connection.first.side, ka);
+            let (ib, jb) = b.side_index(connection.second.side, kb);
+            let (p, q) = (a.point(ia, ja), b.point(ib, jb));
+            if (p.x() - q.x()).hypot(p.y() - q.y()) > tol {
+                return Err("connected points do not coincide");
+            }
+        }
+        Ok(())
+    }
+
+    // finds every pair of block sides that share nodes to within the tolerance and records
+    // the one-to-one connections between them. boundary nodes are bucketed on a spatial hash
+    // so each node is only compared with its neighbours. connections already recorded are
+    // kept, and anything that looks like an interface but does not match node for node is
+    // returned as a diagnostic rather than connected. blocks squashed to a single point are
+    // left out, and nothing is recorded if any of the found connections fails its check
+    pub fn detect_connections(&mut self) -> Result<Vec<InterfaceDiagnostic>, &'static str> {
+        // an absolute tolerance no larger than add_connection will use for any pair of blocks
+        let size = self.blocks.iter().map(diagonal).filter(|&d| d > 0.0).fold(f64::INFINITY, f64::min);
+        let tol = if size.is_finite() { self.tolerance * size } else { 0.0 };
+        // hash cells are kept well above round-off of the coordinates so their keys stay small
+        let cell = tol.max(extent(&self.blocks) * HASH_CELL_FLOOR).max(f64::MIN_POSITIVE);
+
+        let hash = SpatialHash::new(&self.blocks, cell);
+        let mut matches: HashMap<(SideRef, SideRef), HashSet<(usize, usize)>> = HashMap::new();
+        for (a, &(x, y)) in hash.nodes.iter() {
+            for b in hash.near(x, y) {
+                let (p, q) = (a.node(), b.node());
+                let (other_x, other_y) = hash.nodes[&b];
+                if p == q || (x - other_x).hypot(y - other_y) > tol {
+                    continue;
+                }
+                // each pair of sides once, with a self match only counted one way round
+                if a.side_ref() < b.side_ref() || (a.side_ref() == b.side_ref() && a.k < b.k) {
+                    matches.entry((a.side_ref(), b.side_ref())).or_default().insert((a.k, b.k));
+                }
+            }
+        }
+
+        let mut found: Vec<Connection> = Vec::new();
+        let mut keys: Vec<&(SideRef, SideRef)> = matches.keys().collect();
+        keys.sort();
+        for key in keys {
+            for connection in runs(key, &matches[key]) {
+                let exists = self.connections.iter().chain(found.iter()).any(|c| {
+                    (c.first == connection.first && c.second == connection.second)
+                        || (c.first == connection.second && c.second == connection.first)
+                });
+                if !exists {
+                    self.check_connection(&connection)?;
+                    found.push(connection);
+                }
+            }
+        }
+        self.connections.extend(found);
+
+        Ok(self.diagnose(tol, cell))
+    }
+
+    // unconnected boundary nodes that sit part way along another block's side are hanging
+    // nodes of an interface that did not match
+    fn diagnose(&self, tol: f64, cell: f64) -> Vec<InterfaceDiagnostic> {
+        let mut connected: HashSet<(SideRef, usize)> = HashSet::new();
+        for connection in self.connections.iter() {
+            for face in [&connection.first, &connection.second] {
+                for k in face.range.clone() {
+                    connected.insert(((face.block, side_number(face.side)), k));
+                }
+            }
+        }
+
+        let segments = SegmentHash::new(&self.blocks, cell);
+        let mut hanging: HashMap<(SideRef, SideRef), (usize, usize)> = HashMap::new();
+        for (block, grid) in self.blocks.iter().enumerate().filter(|(_, grid)| diagonal(grid) > 0.0) {
+            for side in SIDES {
+                let this = (block, side_number(side));
+                for k in 0..grid.side_len(side) {
+                    if connected.contains(&(this, k)) {
+                        continue;
+                    }
+                    let (i, j) = grid.side_index(side, k);
+                    let point = grid.point(i, j);
+                    for (other, m) in segments.near(point.x(), point.y()) {
+                        if other == this && (m == k || m + 1 == k) {
+                            continue;
+                        }
+                        let other_grid = &self.blocks[other.0];
+                        let (a, b) = (
+                            other_grid.side_index(SIDES[other.1], m),
+                            other_grid.side_index(SIDES[other.1], m + 1),
+                        );
+                        let (a, b) = (other_grid.point(a.0, a.1), other_grid.point(b.0, b.1));
+                        if hangs_on((point.x(), point.y()), (a.x(), a.y()), (b.x(), b.y()), tol) {
+                            let range = hanging.entry((this, other)).or_insert((k, k));
+                            range.0 = range.0.min(k);
+                            range.1 = range.1.max(k);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut keys: Vec<&(SideRef, SideRef)> = hanging.keys().collect();
+        keys.sort();
+        keys.into_iter()
+            .map(|&(this, other)| {
+                let (start, end) = hanging[&(this, other)];
+                let partial = self.connections.iter().any(|c| {
+                    let (a, b) = ((c.first.block, side_number(c.first.side)), (c.second.block, side_number(c.second.side)));
+                    (a, b) == (this, other) || (b, a) == (this, other)
+                });
+                InterfaceDiagnostic {
+                    kind: if partial { DiagnosticKind::PartialMatch } else { DiagnosticKind::NonMatching },
+                    face: BlockFace { block: this.0, side: SIDES[this.1], range: start..=end },
+                    other_block: other.0,
+                    other_side: SIDES[other.1],
+                }
+            })
+            .collect()
+    }
+
+    pub fn blocks(&self) -> &[Grid2D] {
+        &self.blocks
+    }
+
+    pub fn block(&self, index: usize) -> Option<&Grid2D> {
+        self.blocks.get(index)
+    }
+
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn connections(&self) -> &[Connection] {
+        &self.connections
+    }
+
+    // connections touching one block
+    pub fn block_connections(&self, block: usize) -> impl Iterator<Item = &Connection> {
+        self.connections
+            .iter()
+            .filter(move |c| c.first.block == block || c.second.block == block)
+    }
+
+    // total number of grid points over all blocks, counting shared points once per block
+    pub fn num_pts(&self) -> usize {
+        self.blocks.iter().map(|block| block.num_pts()).sum()
+    }
+}
+
+impl Default for MultiBlockGrid2D {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const SIDES: [Side; 4] = [Side::Bottom, Side::Top, Side::Left, Side::Right];
+
+fn side_number(side: Side) -> usize {
+    SIDES.iter().position(|&s| s == side).unwrap_or(0)
+}
+
+// (block, side number), ordered so each pair of sides is only looked at once
+type SideRef = (usize, usize);
+
+// one node on one side of one block. corner nodes appear once for each of their sides
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SideNode {
+    block: usize,
+    side: usize,
+    k: usize,
+    i: usize,
+    j: usize,
+}
+
+impl SideNode {
+    fn side_ref(&self) -> SideRef {
+        (self.block, self.side)
+    }
+
+    fn node(&self) -> (usize, usize, usize) {
+        (self.block, self.i, self.j)
+    }
+}
+
+// smallest hash cell as a fraction of the largest coordinate
+const HASH_CELL_FLOOR: f64 = 1e-12;
+
+// boundary nodes bucketed on a grid of cells at least one tolerance across, so coincident
+// nodes are always in the same or a neighbouring cell
+struct SpatialHash {
+    cell: f64,
+    cells: HashMap<(i64, i64), Vec<SideNode>>,
+    nodes: HashMap<SideNode, (f64, f64)>,
+}
+
+impl SpatialHash {
+    fn new(blocks: &[Grid2D], cell: f64) -> Self {
+        let mut hash = SpatialHash { cell, cells: HashMap::new(), nodes: HashMap::new() };
+        for (block, grid) in blocks.iter().enumerate().filter(|(_, grid)| diagonal(grid) > 0.0) {
+            for (side, &s) in SIDES.iter().enumerate() {
+                for k in 0..grid.side_len(s) {
+                    let (i, j) = grid.side_index(s, k);
+                    let point = grid.point(i, j);
+                    let node = SideNode { block, side, k, i, j };
+                    hash.cells.entry(hash.key(point.x(), point.y())).or_default().push(node);
+                    hash.nodes.insert(node, (point.x(), point.y()));
+                }
+            }
+        }
+        hash
+    }
+
+    fn key(&self, x: f64, y: f64) -> (i64, i64) {
+        ((x / self.cell).floor() as i64, (y / self.cell).floor() as i64)
+    }
+
+    fn near(&self, x: f64, y: f64) -> impl Iterator<Item = SideNode> + '_ {
+        neighbourhood(self.key(x, y))
+            .filter_map(|key| self.cells.get(&key))
+            .flat_map(|nodes| nodes.iter().copied())
+    }
+}
+
+// boundary segments bucketed on every cell their bounding box touches, with cells about
+// the size of a typical segment and never smaller than the node hash cells
+struct SegmentHash {
+    cell: f64,
+    cells: HashMap<(i64, i64), Vec<(SideRef, usize)>>,
+}
+
+impl SegmentHash {
+    fn new(blocks: &[Grid2D], min_cell: f64) -> Self {
+        let mut lengths: Vec<f64> = Vec::new();
+        for grid in blocks {
+            for side in SIDES {
+                for k in 0..grid.side_len(side) - 1 {
+                    let (a, b) = (grid.side_index(side, k), grid.side_index(side, k + 1));
+                    let (a, b) = (grid.point(a.0, a.1), grid.point(b.0, b.1));
+                    lengths.push((b.x() - a.x()).hypot(b.y() - a.y()));
+                }
+            }
+        }
+        lengths.retain(|&length| length > 0.0);
+        lengths.sort_by(f64::total_cmp);
+        let cell = lengths.get(lengths.len() / 2).copied().unwrap_or(1.0).max(min_cell);
+
+        let mut hash = SegmentHash { cell, cells: HashMap::new() };
+        for (block, grid) in blocks.iter().enumerate() {
+            for (side, &s) in SIDES.iter().enumerate() {
+                for k in 0..grid.side_len(s) - 1 {
+                    let (a, b) = (grid.side_index(s, k), grid.side_index(s, k + 1));
+                    let (a, b) = (grid.point(a.0, a.1), grid.point(b.0, b.1));
+                    for key in hash.cells_along((a.x(), a.y()), (b.x(), b.y())) {
+                        hash.cells.entry(key).or_default().push(((block, side), k));
+                    }
+                }
+            }
+        }
+        hash
+    }
+
+    fn key(&self, x: f64, y: f64) -> (i64, i64) {
+        ((x / self.cell).floor() as i64, (y / self.cell).floor() as i64)
+    }
+
+    // the cells a segment passes through, walked from one end to the other one cell border
+    // at a time. a long diagonal segment only lands in a strip of cells rather than every
+    // cell of its bounding box, and any cell it only grazes is a neighbour of one listed
+    fn cells_along(&self, a: (f64, f64), b: (f64, f64)) -> Vec<(i64, i64)> {
+        let (start, end) = (self.key(a.0, a.1), self.key(b.0, b.1));
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let (step_x, step_y) = ((end.0 - start.0).signum(), (end.1 - start.1).signum());
+        // parameter along the segment of the next border crossed in each direction, and
+        // between successive borders
+        let first = |key: i64, step: i64, from: f64, d: f64| {
+            let border = (key + i64::from(step > 0)) as f64 * self.cell;
+            if step == 0 { f64::INFINITY } else { (border - from) / d }
+        };
+        let (mut next_x, mut next_y) = (first(start.0, step_x, a.0, dx), first(start.1, step_y, a.1, dy));
+        let (delta_x, delta_y) = (self.cell / dx.abs(), self.cell / dy.abs());
+
+        // exactly as many steps as borders between the end cells, so round off can not
+        // make the walk miss the last cell
+        let (mut left_x, mut left_y) = ((end.0 - start.0).unsigned_abs(), (end.1 - start.1).unsigned_abs());
+        let mut key = start;
+        let mut cells = vec![key];
+        while left_x + left_y > 0 {
+            if left_y == 0 || (left_x > 0 && next_x < next_y) {
+                key.0 += step_x;
+                next_x += delta_x;
+                left_x -= 1;
+            } else {
+                key.1 += step_y;
+                next_y += delta_y;
+                left_y -= 1;
+            }
+            cells.push(key);
+        }
+        cells
+    }
+
+    // a point within the tolerance of a segment can sit just over a cell border from it
+    fn near(&self, x: f64, y: f64) -> impl Iterator<Item = (SideRef, usize)> + '_ {
+        let mut found: Vec<(SideRef, usize)> = neighbourhood(self.key(x, y))
+            .filter_map(|key| self.cells.get(&key))
+            .flat_map(|segments| segments.iter().copied())
+            .collect();
+        // segments spanning several cells are bucketed more than once
+        found.sort();
+        found.dedup();
+        found.into_iter()
+    }
+}
+
+// the 3x3 block of cells around a key, without stepping past the ends of the key range
+fn neighbourhood((kx, ky): (i64, i64)) -> impl Iterator<Item = (i64, i64)> {
+    (kx.saturating_sub(1)..=kx.saturating_add(1))
+        .flat_map(move |a| (ky.saturating_sub(1)..=ky.saturating_add(1)).map(move |b| (a, b)))
+}
+
+// point p lies on segment ab but clear of both its ends
+fn hangs_on(p: (f64, f64), a: (f64, f64), b: (f64, f64), tol: f64) -> bool {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx.hypot(dy);
+    if length <= 2.0 * tol {
+        return false;
+    }
+    let t = ((p.0 - a.0) * dx + (p.1 - a.1) * dy) / (length * length);
+    let off = ((p.0 - a.0) * dy - (p.1 - a.1) * dx).abs() / length;
+    off <= tol && t * length > tol && (1.0 - t) * length > tol
+}
+
+// turns the matched (k, k') pairs between two sides into connections, one for each run of
+// consecutive points where k' steps the same way every time
+fn runs(key: &(SideRef, SideRef), pairs: &HashSet<(usize, usize)>) -> Vec<Connection> {
+    let ((block_a, side_a), (block_b, side_b)) = *key;
+    let mut sorted: Vec<(usize, usize)> = pairs.iter().copied().collect();
+    sorted.sort();
+
+    let mut connections = Vec::new();
+    for &(k, m) in sorted.iter() {
+        for (orientation, step) in [(Orientation::Aligned, 1_isize), (Orientation::Reversed, -1)] {
+            let before = (k.wrapping_sub(1), m.wrapping_add_signed(-step));
+            let after = |k: usize, m: usize| (k + 1, m.wrapping_add_signed(step));
+            if pairs.contains(&before) || !pairs.contains(&after(k, m)) {
+                continue;
+            }
+            let (mut end_k, mut end_m) = (k, m);
+            while pairs.contains(&after(end_k, end_m)) {
+                (end_k, end_m) = after(end_k, end_m);
+            }
+            let (first, second) = (k..=end_k, m.min(end_m)..=m.max(end_m));
+            let first = BlockFace { block: block_a, side: SIDES[side_a], range: first };
+            let second = BlockFace { block: block_b, side: SIDES[side_b], range: second };
+            if let Ok(connection) = Connection::new(first, second, orientation) {
+                connections.push(connection);
+            }
+        }
+    }
+    connections
+}
+
+fn diagonal(grid: &Grid2D) -> f64 {
+    let (min_x, max_x, min_y, max_y) = grid.extents();
+    (max_x - min_x).hypot(max_y - min_y)
+}
+
+// largest coordinate magnitude over all blocks
+fn extent(blocks: &[Grid2D]) -> f64 {
+    blocks
+        .iter()
+        .map(|grid| {
+            let (min_x, max_x, min_y, max_y) = grid.extents();
+            min_x.abs().max(max_x.abs()).max(min_y.abs()).max(max_y.abs())
+        })
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // nx by ny block of unit cells with its bottom left corner at (x0, y0)
+    fn block(x0: f64, y0: f64, nx: usize, ny: usize) -> Grid2D {
+        let mut grid = Grid2D::new(nx, ny);
+        for j in 0..ny {
+            for i in 0..nx {
+                grid.add_point(x0 + i as f64, y0 + j as f64);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_aligned_connection() {
+        let mut grid = MultiBlockGrid2D::new();
+        let a = grid.add_block(block(0.0, 0.0, 4, 5)).unwrap();
+        let b = grid.add_block(block(3.0, 0.0, 3, 5)).unwrap();
+        let connection = Connection::new(
+            BlockFace::new(a, Side::Right, 0..=4).unwrap(),
+            BlockFace::new(b, Side::Left, 0..=4).unwrap(),
+            Orientation::Aligned,
+        ).unwrap();
+        grid.add_connection(connection).unwrap();
+
+        assert_eq!(grid.num_blocks(), 2);
+        assert_eq!(grid.num_pts(), 35);
+        assert_eq!(grid.block_connections(b).count(), 1);
+        let pairs: Vec<(usize, usize)> = grid.connections()[0].pairs().collect();
+        assert_eq!(pairs, vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+    }
+
+    #[test]
+    fn test_reversed_partial_connection() {
+        // second block sits on top of part of the first one, with its j direction flipped
+        let mut flipped = Grid2D::new(3, 3);
+        for j in 0..3 {
+            for i in 0..3 {
+                flipped.add_point(1.0 + i as f64, 6.0 - j as f64);
+            }
+        }
+        let mut grid = MultiBlockGrid2D::new();
+        grid.add_block(block(0.0, 0.0, 6, 5)).unwrap();
+        grid.add_block(flipped).unwrap();
+
+        let aligned = Connection::new(
+            BlockFace::new(0, Side::Top, 1..=3).unwrap(),
+            BlockFace::new(1, Side::Top, 0..=2).unwrap(),
+            Orientation::Aligned,
+        ).unwrap();
+        grid.add_connection(aligned).unwrap();
+
+        // same points walked backwards on one side do not coincide
+        let reversed = Connection::new(
+            BlockFace::new(0, Side::Top, 1..=3).unwrap(),
+            BlockFace::new(1, Side::Top, 0..=2).unwrap(),
+            Orientation::Reversed,
+        ).unwrap();
+        assert!(grid.add_connection(reversed).is_err());
+    }
+
+    #[test]
+    fn test_rejects_bad_connections() {
+        let mut grid = MultiBlockGrid2D::new();
+        grid.add_block(block(0.0, 0.0, 4, 4)).unwrap();
+        grid.add_block(block(5.0, 0.0, 4, 4)).unwrap();
+
+        // different lengths, self overlap, missing block, out of range, gap between blocks
+        assert!(Connection::new(
+            BlockFace::new(0, Side::Right, 0..=3).unwrap(),
+            BlockFace::new(1, Side::Left, 0..=2).unwrap(),
+            Orientation::Aligned,
+        ).is_err());
+        assert!(Connection::new(
+            BlockFace::new(0, Side::Bottom, 0..=2).unwrap(),
+            BlockFace::new(0, Side::Bottom, 1..=3).unwrap(),
+            Orientation::Reversed,
+        ).is_err());
+        let face = |block, side, range| BlockFace::new(block, side, range).unwrap();
+        let missing = Connection::new(face(0, Side::Right, 0..=3), face(2, Side::Left, 0..=3), Orientation::Aligned).unwrap();
+        assert!(grid.add_connection(missing).is_err());
+        let outside = Connection::new(face(0, Side::Right, 1..=4), face(1, Side::Left, 0..=3), Orientation::Aligned).unwrap();
+        assert!(grid.add_connection(outside).is_err());
+        let gap = Connection::new(face(0, Side::Right, 0..=3), face(1, Side::Left, 0..=3), Orientation::Aligned).unwrap();
+        assert!(grid.add_connection(gap).is_err());
+        assert!(grid.connections().is_empty());
+
+        assert!(grid.add_block(Grid2D::new(2, 2)).is_err());
+    }
+
+    // block with the given x and y stations, x varying along i
+    fn stations(xs: &[f64], ys: &[f64]) -> Grid2D {
+        let mut grid = Grid2D::new(xs.len(), ys.len());
+        for &y in ys {
+            for &x in xs {
+                grid.add_point(x, y);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_detects_aligned_and_reversed_interfaces() {
+        let mut flipped = Grid2D::new(3, 5);
+        for j in 0..5 {
+            for i in 0..3 {
+                flipped.add_point(-2.0 + i as f64, 4.0 - j as f64);
+            }
+        }
+        let mut grid = MultiBlockGrid2D::new();
+        grid.add_block(block(0.0, 0.0, 4, 5)).unwrap();
+        grid.add_block(block(3.0, 0.0, 3, 5)).unwrap();
+        grid.add_block(flipped).unwrap();
+
+        let diagnostics = grid.detect_connections().unwrap();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(grid.connections().len(), 2);
+
+        let face = |block, side| BlockFace::new(block, side, 0..=4).unwrap();
+        let right = &grid.connections()[0];
+        assert_eq!((right.first(), right.second()), (&face(0, Side::Left), &face(2, Side::Right)));
+        assert_eq!(right.orientation(), Orientation::Reversed);
+        let left = &grid.connections()[1];
+        assert_eq!((left.first(), left.second()), (&face(0, Side::Right), &face(1, Side::Left)));
+        assert_eq!(left.orientation(), Orientation::Aligned);
+
+        // running it again does not duplicate anything
+        assert!(grid.detect_connections().unwrap().is_empty());
+        assert_eq!(grid.connections().len(), 2);
+    }
+
+    #[test]
+    fn test_detects_c_grid_wake_cut_and_o_grid_seam() {
+        use crate::generator::topology::{CGridGenerator, OGridGenerator};
+
+        let circle: Vec<_> = (0..=24)
+            .map(|k| {
+                let theta = 2.0 * std::f64::consts::PI * (k % 24) as f64 / 24.0;
+                crate::utils::geometry::Cartesian2D::new(theta.cos(), 0.3 * theta.sin())
+            })
+            .collect();
+        // second body well clear of the first one's far field
+        let shifted = circle.iter().map(|p| crate::utils::geometry::Cartesian2D::new(p.x() + 100.0, p.y())).collect();
+        let mut grid = MultiBlockGrid2D::new();
+        grid.add_block(OGridGenerator::new(circle, 9).unwrap().generate().unwrap()).unwrap();
+        grid.add_block(CGridGenerator::new(shifted, 7, 9).unwrap().generate().unwrap()).unwrap();
+        assert!(grid.detect_connections().unwrap().is_empty());
+
+        let seam = &grid.connections()[0];
+        assert_eq!((seam.first().block(), seam.first().side()), (0, Side::Left));
+        assert_eq!((seam.second().block(), seam.second().side()), (0, Side::Right));
+        assert_eq!(seam.orientation(), Orientation::Aligned);
+
+        let nx = grid.block(1).unwrap().nx();
+        let wake = &grid.connections()[1];
+        assert_eq!(wake.first(), &BlockFace::new(1, Side::Bottom, 0..=6).unwrap());
+        assert_eq!(wake.second(), &BlockFace::new(1, Side::Bottom, nx - 7..=nx - 1).unwrap());
+        assert_eq!(wake.orientation(), Orientation::Reversed);
+    }
+
+    #[test]
+    fn test_reports_non_matching_interface() {
+        // twice the spacing on one side of the interface
+        let mut grid = MultiBlockGrid2D::new();
+        grid.add_block(stations(&[0.0, 1.0, 2.0], &[0.0, 1.0, 2.0, 3.0, 4.0])).unwrap();
+        grid.add_block(stations(&[2.0, 3.0], &[0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0])).unwrap();
+
+        let diagnostics = grid.detect_connections().unwrap();
+        assert!(grid.connections().is_empty());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind(), DiagnosticKind::NonMatching);
+        assert_eq!(diagnostics[0].face(), &BlockFace::new(1, Side::Left, 1..=7).unwrap());
+        assert_eq!((diagnostics[0].other_block(), diagnostics[0].other_side()), (0, Side::Right));
+        assert!(diagnostics[0].to_string().starts_with("non-matching interface: block 1 Left"));
+    }
+
+    #[test]
+    fn test_reports_partially_matching_interface() {
+        let mut grid = MultiBlockGrid2D::new();
+        grid.add_block(stations(&[0.0, 1.0, 2.0], &[0.0, 1.0, 2.0, 3.0, 4.0])).unwrap();
+        grid.add_block(stations(&[2.0, 3.0], &[0.0, 1.0, 2.0, 2.5, 3.5, 4.0])).unwrap();
+
+        let diagnostics = grid.detect_connections().unwrap();
+        assert_eq!(grid.connections().len(), 1);
+        assert_eq!(grid.connections()[0].first(), &BlockFace::new(0, Side::Right, 0..=2).unwrap());
+        assert_eq!(grid.connections()[0].second(), &BlockFace::new(1, Side::Left, 0..=2).unwrap());
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.kind() == DiagnosticKind::PartialMatch));
+        assert_eq!(diagnostics[0].face(), &BlockFace::new(0, Side::Right, 3..=3).unwrap());
+        assert_eq!(diagnostics[1].face(), &BlockFace::new(1, Side::Left, 3..=4).unwrap());
+    }
+
+    #[test]
+    fn test_detection_skips_degenerate_block() {
+        // blocks squashed to one point or next to it must not shrink the hash cells to nothing
+        let mut point = Grid2D::new(2, 2);
+        for _ in 0..4 {
+            point.add_point(1.0, 1.0);
+        }
+        let tiny = stations(&[1e-300, 2e-300], &[1e-300, 2e-300]);
+        let mut grid = MultiBlockGrid2D::new();
+        grid.add_block(block(0.0, 0.0, 4, 5)).unwrap();
+        grid.add_block(point).unwrap();
+        grid.add_block(tiny).unwrap();
+        grid.add_block(block(3.0, 0.0, 3, 5)).unwrap();
+
+        assert!(grid.detect_connections().unwrap().is_empty());
+        assert_eq!(grid.connections().len(), 1);
+        assert_eq!(grid.connections()[0].first(), &BlockFace::new(0, Side::Right, 0..=4).unwrap());
+        assert_eq!(grid.connections()[0].second(), &BlockFace::new(3, Side::Left, 0..=4).unwrap());
+    }
+
+    #[test]
+    fn test_reports_hanging_nodes_across_cell_border() {
+        // the second block's side sits just short of x = 2, in the next hash cell over from
+        // the first block's side, with its nodes half way between the first block's nodes
+        let mut grid = MultiBlockGrid2D::new();
+        grid.set_tolerance(1e-3).unwrap();
+        grid.add_block(stations(&[0.0, 1.0, 2.0], &[0.0, 1.0, 2.0, 3.0, 4.0])).unwrap();
+        grid.add_block(stations(&[2.0 - 1e-4, 3.0], &[0.5, 1.5, 2.5, 3.5])).unwrap();
+
+        let diagnostics = grid.detect_connections().unwrap();
+        assert!(grid.connections().is_empty());
+        // the corner nodes of the second block are reported for its top and bottom sides too
+        let faces: Vec<&BlockFace> = diagnostics.iter().map(|d| d.face()).collect();
+        assert_eq!(faces, vec![
+            &BlockFace::new(0, Side::Right, 1..=3).unwrap(),
+            &BlockFace::new(1, Side::Bottom, 0..=0).unwrap(),
+            &BlockFace::new(1, Side::Top, 0..=0).unwrap(),
+            &BlockFace::new(1, Side::Left, 0..=3).unwrap(),
+        ]);
+        assert!(diagnostics.iter().all(|d| d.kind() == DiagnosticKind::NonMatching));
+    }
+
+    #[test]
+    fn test_segments_are_bucketed_along_their_length() {
+        // a long diagonal far field segment next to fine wall spacing only fills a strip of
+        // cells, not its whole bounding box
+        let mut xs: Vec<f64> = (0..200).map(|k| 1e-3 * k as f64).collect();
+        xs.push(100.0);
+        let mut grid = Grid2D::new(xs.len(), 2);
+        for &x in xs.iter() {
+            grid.add_point(x, 0.0);
+        }
+        for &x in xs.iter() {
+            grid.add_point(x, if x > 1.0 { 50.0 } else { 1e-3 });
+        }
+        let hash = SegmentHash::new(&[grid], 0.0);
+        let entries: usize = hash.cells.values().map(Vec::len).sum();
+        // the bounding box of the long segment alone covers about 5e9 cells
+        assert!(entries < 1_000_000, "{} cell entries", entries);
+
+        let cells = hash.cells_along((0.5 * hash.cell, 0.5 * hash.cell), (100.5 * hash.cell, 50.5 * hash.cell));
+        assert_eq!(cells.len(), 151);
+        assert_eq!((cells[0], cells[150]), ((0, 0), (100, 50)));
+        assert!(cells.windows(2).all(|w| (w[1].0 - w[0].0) + (w[1].1 - w[0].1) == 1));
+    }
+}
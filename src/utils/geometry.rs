@@ -2,18 +2,37 @@
 
 use num_complex::Complex;
 
+use crate::utils::numerics::{
+    bisect, integrate, least_squares, mat2_inverse, mat2_vec, solve_block_tridiagonal, solve_periodic_block_tridiagonal,
+    Mat2, Vec2,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Cartesian2D {
     x: f64,
     y: f64,
 }
 
+impl Cartesian2D {
+    pub fn new(x: f64, y: f64) -> Self {
+        Cartesian2D { x, y }
+    }
+
+    pub fn x(&self) -> f64 { self.x }
+    pub fn y(&self) -> f64 { self.y }
+
+    pub fn distance(&self, other: &Cartesian2D) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
 pub trait Line2D {
     fn eqn(&self) -> impl Fn(f64) -> f64;
     fn solve(&self, x: f64) -> f64;
     fn plot(&self) -> Result<(), Box<dyn std::error::Error>> {
         self.plot_bounded((-10.0, 10.0))
     }
-    fn plot_bounded(&self, x_range: (f64, f64)) -> Result<(), Box<dyn std::error::Error>> {
+    fn plot_bounded(&self, _x_range: (f64, f64)) -> Result<(), Box<dyn std::error::Error>> {
         todo!()
     }
 }
@@ -30,7 +49,7 @@ impl StraightLine2D {
     }
 
     pub fn from_cartesian_points(p1: Cartesian2D, p2: Cartesian2D) -> Self {
-        let m: f64 = (p2.y - p1.y) / (p2.x - p2.x);
+        let m: f64 = (p2.y - p1.y) / (p2.x - p1.x);
         let c: f64 = p1.y - m * p1.x;
         StraightLine2D { m, c }
     }
@@ -67,6 +86,7 @@ impl std::fmt::Debug for StraightLine2D {
 }
 
 // polynomial stuff // 
+#[derive(Clone)]
 pub struct Polynomial {
     coefs: Vec<f64>,
 }
@@ -144,6 +164,14 @@ impl Polynomial {
         roots
     }
 
+    pub fn derivative(&self) -> Polynomial {
+        let n = self.order();
+        if n == 0 {
+            return Polynomial::new(vec![0.0]);
+        }
+        Polynomial::new(self.coefs[..n].iter().enumerate().map(|(i, c)| c * (n - i) as f64).collect())
+    }
+
     pub fn real_roots(&self) -> Vec<f64> {
         // tolerance for considering a complex root to be real
         let tol: f64 = 1e-8;
@@ -220,105 +248,2530 @@ impl std::fmt::Debug for Polynomial {
     }
 }
 
-// b-spline stuff // 
+// parametric curve stuff //
+// a curve traced by a parameter t over a domain, so it can be vertical, closed or loop back
+// on itself. normals are the unit tangent turned a quarter anticlockwise, so they point
+// into a body traced anticlockwise, and curvature is positive where the curve turns left
+pub trait ParametricCurve2D {
+    fn domain(&self) -> (f64, f64);
+    fn point(&self, t: f64) -> Cartesian2D;
+    fn first_derivative(&self, t: f64) -> Cartesian2D;
+    fn second_derivative(&self, t: f64) -> Cartesian2D;
+
+    // unit tangent, zero where the curve stops
+    fn tangent(&self, t: f64) -> Cartesian2D {
+        let d = self.first_derivative(t);
+        let speed = d.x.hypot(d.y);
+        if speed == 0.0 {
+            return Cartesian2D::new(0.0, 0.0);
+        }
+        Cartesian2D::new(d.x / speed, d.y / speed)
+    }
+
+    fn normal(&self, t: f64) -> Cartesian2D {
+        let tangent = self.tangent(t);
+        Cartesian2D::new(-tangent.y, tangent.x)
+    }
+
+    fn curvature(&self, t: f64) -> f64 {
+        let (d1, d2) = (self.first_derivative(t), self.second_derivative(t));
+        let speed = d1.x.hypot(d1.y);
+        if speed == 0.0 {
+            return 0.0;
+        }
+        cross(&d1, &d2) / speed.powi(3)
+    }
+
+    // parameters where derivatives may jump, such as spline knots, from the start of the
+    // domain to its end. integrals along the curve are taken piece by piece between them
+    fn breakpoints(&self) -> Vec<f64> {
+        let (start, end) = self.domain();
+        vec![start, end]
+    }
+
+    fn arc_length(&self) -> f64 {
+        let (start, end) = self.domain();
+        self.arc_length_between(start, end)
+    }
+
+    // length along the curve from t0 to t1, negative if t1 comes first
+    fn arc_length_between(&self, t0: f64, t1: f64) -> f64 {
+        integrate_pieces(&|t| self.speed(t), &self.breakpoints(), t0, t1)
+    }
+
+    // rate of arc length with the parameter
+    fn speed(&self, t: f64) -> f64 {
+        let d = self.first_derivative(t);
+        d.x.hypot(d.y)
+    }
+
+    // the parameter a length s along the curve from its start
+    fn parameter_at_length(&self, s: f64) -> Result<f64, &'static str> {
+        let length = self.arc_length();
+        if !(0.0..=length).contains(&s) {
+            return Err("length is beyond the ends of the curve");
+        }
+        Ok(invert_integral(|t| self.speed(t), &self.breakpoints(), &[s / length])?[0])
+    }
+
+    // n points from one end to the other, spaced so that more go where the curve bends. the
+    // density along the curve is 1 + weight |curvature| length, so weight 0 spaces them evenly
+    // by arc length and the clustering does not depend on the size of the curve
+    fn distribute_by_curvature(&self, n: usize, weight: f64) -> Result<Vec<Cartesian2D>, &'static str> {
+        if n < 2 {
+            return Err("a distribution needs at least two points");
+        }
+        if weight < 0.0 {
+            return Err("curvature weight must not be negative");
+        }
+        let length = self.arc_length();
+        let density = |t: f64| self.speed(t) * (1.0 + weight * length * self.curvature(t).abs());
+        let fractions: Vec<f64> = (0..n).map(|k| k as f64 / (n - 1) as f64).collect();
+        let parameters = invert_integral(density, &self.breakpoints(), &fractions)?;
+        Ok(parameters.into_iter().map(|t| self.point(t)).collect())
+    }
+}
+
+// relative accuracy of arc lengths found by quadrature
+const ARC_LENGTH_TOL: f64 = 1e-12;
+
+// integral from a to b of a density that may jump at the breakpoints
+fn integrate_pieces(density: &impl Fn(f64) -> f64, breakpoints: &[f64], a: f64, b: f64) -> f64 {
+    if b < a {
+        return -integrate_pieces(density, breakpoints, b, a);
+    }
+    breakpoints
+        .windows(2)
+        .map(|w| (w[0].max(a), w[1].min(b)))
+        .filter(|(lo, hi)| hi > lo)
+        .map(|(lo, hi)| integrate(density, lo, hi, ARC_LENGTH_TOL))
+        .sum()
+}
+
+// the parameters over the breakpoints' span at which the integral of a positive density
+// reaches each of the increasing fractions of its total. each is bracketed by the one
+// before it
+pub(crate) fn invert_integral(density: impl Fn(f64) -> f64, breakpoints: &[f64], fractions: &[f64]) -> Result<Vec<f64>, &'static str> {
+    let (start, end) = (breakpoints[0], breakpoints[breakpoints.len() - 1]);
+    let total = integrate_pieces(&density, breakpoints, start, end);
+    if total <= 0.0 || !total.is_finite() {
+        return Err("curve has no length");
+    }
+    let (mut t, mut reached) = (start, 0.0);
+    let mut parameters = Vec::with_capacity(fractions.len());
+    for &fraction in fractions {
+        if !(0.0..=1.0).contains(&fraction) || fraction * total < reached - ARC_LENGTH_TOL * total {
+            return Err("fractions must increase from 0 to 1");
+        }
+        let wanted = fraction * total - reached;
+        if fraction == 1.0 {
+            t = end;
+        } else if wanted > 0.0 {
+            t = solve_integral(&density, breakpoints, t, end, wanted)?;
+        }
+        reached = fraction * total;
+        parameters.push(t);
+    }
+    Ok(parameters)
+}
+
+// the u in [start, end] where the integral of the density from start reaches wanted. newton
+// steps, as the density is the derivative, falling back to halving the bracket when a step
+// leaves it
+fn solve_integral(density: &impl Fn(f64) -> f64, breakpoints: &[f64], start: f64, end: f64, wanted: f64) -> Result<f64, &'static str> {
+    let integral = |u: f64| integrate_pieces(density, breakpoints, start, u);
+    let (mut lo, mut hi) = (start, end);
+    let mut u = start + (end - start) * (wanted / integral(end)).min(1.0);
+    for _ in 0..MAX_INVERSION_STEPS {
+        let error = integral(u) - wanted;
+        if error.abs() <= ARC_LENGTH_TOL * wanted {
+            return Ok(u);
+        }
+        if error > 0.0 {
+            hi = u;
+        } else {
+            lo = u;
+        }
+        if hi - lo <= PARAMETER_TOL * u.abs().max(1.0) {
+            return Ok(u);
+        }
+        let slope = density(u);
+        let step = if slope > 0.0 { u - error / slope } else { f64::NAN };
+        u = if step > lo && step < hi { step } else { 0.5 * (lo + hi) };
+    }
+    bisect(|u| integral(u) - wanted, lo, hi, PARAMETER_TOL)
+}
+
+// accuracy of parameters found by inverting arc length, relative to their size
+const PARAMETER_TOL: f64 = 1e-13;
+const MAX_INVERSION_STEPS: usize = 50;
+
+// straight segment from start (t = 0) to end (t = 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment2D {
+    start: Cartesian2D,
+    end: Cartesian2D,
+}
+
+impl Segment2D {
+    pub fn new(start: Cartesian2D, end: Cartesian2D) -> Self {
+        Segment2D { start, end }
+    }
+
+    pub fn start(&self) -> Cartesian2D {
+        self.start
+    }
+
+    pub fn end(&self) -> Cartesian2D {
+        self.end
+    }
+}
+
+impl ParametricCurve2D for Segment2D {
+    fn domain(&self) -> (f64, f64) {
+        (0.0, 1.0)
+    }
+
+    fn point(&self, t: f64) -> Cartesian2D {
+        Cartesian2D::new(self.start.x + t * (self.end.x - self.start.x), self.start.y + t * (self.end.y - self.start.y))
+    }
+
+    fn first_derivative(&self, _t: f64) -> Cartesian2D {
+        Cartesian2D::new(self.end.x - self.start.x, self.end.y - self.start.y)
+    }
+
+    fn second_derivative(&self, _t: f64) -> Cartesian2D {
+        Cartesian2D::new(0.0, 0.0)
+    }
+
+    fn arc_length_between(&self, t0: f64, t1: f64) -> f64 {
+        (t1 - t0) * self.start.distance(&self.end)
+    }
+}
+
+// the graph of a polynomial between two x values, with t = x
+#[derive(Debug, Clone)]
+pub struct PolynomialCurve {
+    polynomial: Polynomial,
+    slope: Polynomial,
+    bend: Polynomial,
+    range: (f64, f64),
+}
+
+impl PolynomialCurve {
+    pub fn new(polynomial: Polynomial, x0: f64, x1: f64) -> Result<Self, &'static str> {
+        if x0 >= x1 {
+            return Err("polynomial curve needs an increasing x range");
+        }
+        let slope = polynomial.derivative();
+        let bend = slope.derivative();
+        Ok(PolynomialCurve { polynomial, slope, bend, range: (x0, x1) })
+    }
+}
+
+impl ParametricCurve2D for PolynomialCurve {
+    fn domain(&self) -> (f64, f64) {
+        self.range
+    }
+
+    fn point(&self, t: f64) -> Cartesian2D {
+        Cartesian2D::new(t, self.polynomial.solve(t))
+    }
+
+    fn first_derivative(&self, t: f64) -> Cartesian2D {
+        Cartesian2D::new(1.0, self.slope.solve(t))
+    }
+
+    fn second_derivative(&self, t: f64) -> Cartesian2D {
+        Cartesian2D::new(0.0, self.bend.solve(t))
+    }
+}
+
+// straight segments through a list of points, with t the fraction of the length along it.
+// corners have no curvature, and their tangent is that of the segment after them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline2D {
+    points: Vec<Cartesian2D>,
+    lengths: Vec<f64>,
+}
+
+impl Polyline2D {
+    pub fn new(points: Vec<Cartesian2D>) -> Result<Self, &'static str> {
+        if points.len() < 2 {
+            return Err("polyline needs at least two points");
+        }
+        let mut lengths = vec![0.0];
+        for w in points.windows(2) {
+            lengths.push(lengths[lengths.len() - 1] + w[0].distance(&w[1]));
+        }
+        if lengths[lengths.len() - 1] <= 0.0 {
+            return Err("polyline points must not all coincide");
+        }
+        Ok(Polyline2D { points, lengths })
+    }
+
+    pub fn points(&self) -> &[Cartesian2D] {
+        &self.points
+    }
+
+    // whether the last point is back at the first
+    pub fn is_closed(&self) -> bool {
+        self.points[0] == self.points[self.points.len() - 1]
+    }
+
+    // segment holding t and how far along it t is, from 0 to 1. repeated points make zero
+    // length segments, which are never picked, so at the very end this steps back to the
+    // last segment with some length
+    fn locate(&self, t: f64) -> (usize, f64) {
+        let total = self.lengths[self.lengths.len() - 1];
+        let s = t.clamp(0.0, 1.0) * total;
+        let mut k = (self.lengths.partition_point(|&l| l <= s).max(1) - 1).min(self.points.len() - 2);
+        while self.lengths[k + 1] <= self.lengths[k] {
+            k -= 1;
+        }
+        let length = self.lengths[k + 1] - self.lengths[k];
+        (k, ((s - self.lengths[k]) / length).min(1.0))
+    }
+}
+
+impl ParametricCurve2D for Polyline2D {
+    fn domain(&self) -> (f64, f64) {
+        (0.0, 1.0)
+    }
+
+    fn point(&self, t: f64) -> Cartesian2D {
+        let (k, f) = self.locate(t);
+        Segment2D::new(self.points[k], self.points[k + 1]).point(f)
+    }
+
+    fn first_derivative(&self, t: f64) -> Cartesian2D {
+        let (k, _) = self.locate(t);
+        let (a, b) = (self.points[k], self.points[k + 1]);
+        let scale = self.lengths[self.lengths.len() - 1] / a.distance(&b);
+        Cartesian2D::new(scale * (b.x - a.x), scale * (b.y - a.y))
+    }
+
+    fn second_derivative(&self, _t: f64) -> Cartesian2D {
+        Cartesian2D::new(0.0, 0.0)
+    }
+
+    fn breakpoints(&self) -> Vec<f64> {
+        let total = self.lengths[self.lengths.len() - 1];
+        self.lengths.iter().map(|l| l / total).collect()
+    }
+
+    fn arc_length_between(&self, t0: f64, t1: f64) -> f64 {
+        (t1 - t0) * self.lengths[self.lengths.len() - 1]
+    }
+}
+
+// b-spline stuff //
+// how data points are spread along the parameter of a curve fitted through them: by the
+// distance between them, or by its square root, which follows sharp turns better
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameterisation {
+    ChordLength,
+    Centripetal,
+}
+
+impl Parameterisation {
+    // parameters from 0 to 1, one per point
+    pub fn parameters(&self, points: &[Cartesian2D]) -> Result<Vec<f64>, &'static str> {
+        let mut parameters = vec![0.0];
+        for w in points.windows(2) {
+            let step = match self {
+                Parameterisation::ChordLength => w[0].distance(&w[1]),
+                Parameterisation::Centripetal => w[0].distance(&w[1]).sqrt(),
+            };
+            parameters.push(parameters[parameters.len() - 1] + step);
+        }
+        let total = parameters[parameters.len() - 1];
+        if total <= 0.0 {
+            return Err("points must not all coincide");
+        }
+        Ok(parameters.iter().map(|t| t / total).collect())
+    }
+}
+
+// a clamped b-spline: the curve starts at the first control point and ends at the last
+#[derive(Debug, Clone, PartialEq)]
 pub struct BSpline {
     control_points: Vec<Cartesian2D>,
     knots: Vec<f64>,
-    degree: usize,  
+    degree: usize,
 }
 
 impl BSpline {
-    pub fn lsq(data_points: Vec<Cartesian2D>, degree: usize, n_control_points: usize) -> Self {
-        assert!(data_points.len() > degree, "number of data points must be greater than the degree");
-        assert!(n_control_points > degree, "number of control points must be greater than the degree");
-        
-        todo!()
+    pub fn new(control_points: Vec<Cartesian2D>, knots: Vec<f64>, degree: usize) -> Result<Self, &'static str> {
+        if control_points.len() <= degree {
+            return Err("b-spline needs more control points than its degree");
+        }
+        if knots.len() != control_points.len() + degree + 1 {
+            return Err("b-spline needs as many knots as control points plus degree plus one");
+        }
+        if knots.windows(2).any(|w| w[1] < w[0]) {
+            return Err("b-spline knots must not decrease");
+        }
+        if knots[degree] >= knots[control_points.len()] {
+            return Err("b-spline parameter domain is empty");
+        }
+        Ok(BSpline { control_points, knots, degree })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use num_complex::Complex;
+    // least squares fit through the data points with clamped knots spread so that every
+    // knot span holds some data. the curve passes through the first and last data points
+    pub fn lsq(
+        data_points: Vec<Cartesian2D>,
+        degree: usize,
+        n_control_points: usize,
+        parameterisation: Parameterisation,
+    ) -> Result<Self, &'static str> {
+        if data_points.len() <= degree {
+            return Err("number of data points must be greater than the degree");
+        }
+        if n_control_points <= degree {
+            return Err("number of control points must be greater than the degree");
+        }
+        if n_control_points > data_points.len() {
+            return Err("cannot have more control points than data points");
+        }
+        let parameters = parameterisation.parameters(&data_points)?;
+        let (m, n, p) = (data_points.len() - 1, n_control_points - 1, degree);
 
-    const TOL: f64 = 1e-6;
+        // interior knots average the parameters they fall between
+        let mut knots = vec![0.0; p + 1];
+        let d = (m + 1) as f64 / (n - p + 1) as f64;
+        for j in 1..=n - p {
+            let i = (j as f64 * d).floor() as usize;
+            let alpha = j as f64 * d - i as f64;
+            knots.push((1.0 - alpha) * parameters[i - 1] + alpha * parameters[i]);
+        }
+        knots.extend(std::iter::repeat_n(1.0, p + 1));
 
-    fn poly_near_zero(poly: &Polynomial, root: Complex<f64>) -> bool {
-        let f_val = (poly.eqn())(root.re);
-        f_val.abs() < TOL
+        let (first, last) = (data_points[0], data_points[m]);
+        let mut control_points = vec![first; n + 1];
+        control_points[n] = last;
+        if n > 1 {
+            let mut rows = Vec::with_capacity(m - 1);
+            let (mut rhs_x, mut rhs_y) = (Vec::with_capacity(m - 1), Vec::with_capacity(m - 1));
+            for k in 1..m {
+                let row = basis_row(&knots, p, n, parameters[k]);
+                let q = data_points[k];
+                rhs_x.push(q.x - row[0] * first.x - row[n] * last.x);
+                rhs_y.push(q.y - row[0] * first.y - row[n] * last.y);
+                rows.push(row[1..n].to_vec());
+            }
+            let xs = least_squares(&rows, &rhs_x).map_err(|_| "b-spline fit is singular, try fewer control points")?;
+            let ys = least_squares(&rows, &rhs_y).map_err(|_| "b-spline fit is singular, try fewer control points")?;
+            for (k, (x, y)) in xs.into_iter().zip(ys).enumerate() {
+                control_points[k + 1] = Cartesian2D::new(x, y);
+            }
+        }
+        BSpline::new(control_points, knots, degree)
     }
 
-    #[test]
-    fn test_quadratic_roots() {
-        // polynomial: x^2 - 5x + 6 = (x - 2)(x - 3)
-        let poly = Polynomial::new(vec![1.0, -5.0, 6.0]);
-        let roots = poly.roots();
-        assert_eq!(roots.len(), 2);
-        let expected_roots = vec![
-            Complex::new(2.0, 0.0),
-            Complex::new(3.0, 0.0),
-        ];
-        for exp in expected_roots {
-            let found = roots.iter().any(|r| (r - exp).norm() < TOL);
-            assert!(found, "expected root {} not found", exp);
+    pub fn control_points(&self) -> &[Cartesian2D] {
+        &self.control_points
+    }
+
+    pub fn knots(&self) -> &[f64] {
+        &self.knots
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    // range of the parameter over which the curve is defined
+    pub fn domain(&self) -> (f64, f64) {
+        (self.knots[self.degree], self.knots[self.control_points.len()])
+    }
+
+    // de boor's algorithm, u is clamped to the domain
+    pub fn point(&self, u: f64) -> Cartesian2D {
+        de_boor(&self.control_points, &self.knots, self.degree, u)
+    }
+
+    pub fn first_derivative(&self, u: f64) -> Cartesian2D {
+        self.derivative().point(u)
+    }
+
+    pub fn second_derivative(&self, u: f64) -> Cartesian2D {
+        self.derivative().derivative().point(u)
+    }
+
+    // the derivative curve, one degree lower on the knots without their ends. a degree 0
+    // spline has a zero derivative
+    pub fn derivative(&self) -> BSpline {
+        let p = self.degree;
+        if p == 0 {
+            let zero = vec![Cartesian2D::new(0.0, 0.0); self.control_points.len()];
+            return BSpline { control_points: zero, knots: self.knots.clone(), degree: 0 };
         }
+        let control_points = self
+            .control_points
+            .windows(2)
+            .enumerate()
+            .map(|(i, w)| {
+                let span = self.knots[i + p + 1] - self.knots[i + 1];
+                let factor = if span > 0.0 { p as f64 / span } else { 0.0 };
+                Cartesian2D::new(factor * (w[1].x - w[0].x), factor * (w[1].y - w[0].y))
+            })
+            .collect();
+        let knots = self.knots[1..self.knots.len() - 1].to_vec();
+        BSpline { control_points, knots, degree: p - 1 }
     }
 
-    #[test]
-    fn test_cubic_roots() {
-        // polynomial: x^3 - 6x^2 + 11x - 6 = (x - 1)(x - 2)(x - 3)
-        let poly = Polynomial::new(vec![1.0, -6.0, 11.0, -6.0]);
-        let roots = poly.roots();
-        assert_eq!(roots.len(), 3);
-        let expected_roots = vec![
-            Complex::new(1.0, 0.0),
-            Complex::new(2.0, 0.0),
-            Complex::new(3.0, 0.0),
-        ];
-        for exp in expected_roots {
-            let found = roots.iter().any(|r| (r - exp).norm() < TOL);
-            assert!(found, "expected root {} not found", exp);
+    // boehm's algorithm, adds one knot at u inside the domain without changing the curve
+    pub fn insert_knot(&mut self, u: f64) -> Result<(), &'static str> {
+        let (start, end) = self.domain();
+        if u <= start || u >= end {
+            return Err("knot must be inside the b-spline domain");
+        }
+        let p = self.degree;
+        let multiplicity = self.knots.iter().filter(|&&k| k == u).count();
+        if multiplicity >= p {
+            return Err("knot already has the highest multiplicity the degree allows");
+        }
+        let k = find_span(&self.knots, p, self.control_points.len() - 1, u);
+
+        let old = &self.control_points;
+        let mut control_points = Vec::with_capacity(old.len() + 1);
+        control_points.extend_from_slice(&old[..=k - p]);
+        for i in k - p + 1..=k - multiplicity {
+            let alpha = (u - self.knots[i]) / (self.knots[i + p] - self.knots[i]);
+            let (a, b) = (old[i - 1], old[i]);
+            control_points.push(Cartesian2D::new((1.0 - alpha) * a.x + alpha * b.x, (1.0 - alpha) * a.y + alpha * b.y));
+        }
+        control_points.extend_from_slice(&old[k - multiplicity..]);
+
+        self.knots.insert(k + 1, u);
+        self.control_points = control_points;
+        Ok(())
+    }
+
+    // the same curve one degree higher. every distinct knot gains one in multiplicity, and
+    // as the new spline space holds the old curve exactly, interpolating it at the greville
+    // points of the new knots gives the new control points
+    pub fn elevate_degree(&self) -> Result<BSpline, &'static str> {
+        let p = self.degree + 1;
+        let mut knots = Vec::with_capacity(self.knots.len() * 2);
+        for (k, &u) in self.knots.iter().enumerate() {
+            knots.push(u);
+            if k + 1 == self.knots.len() || self.knots[k + 1] != u {
+                knots.push(u);
+            }
         }
+        let n = knots.len() - p - 2;
+
+        let greville: Vec<f64> = (0..=n).map(|i| knots[i + 1..=i + p].iter().sum::<f64>() / p as f64).collect();
+        let rows: Vec<Vec<f64>> = greville.iter().map(|&u| basis_row(&knots, p, n, u)).collect();
+        let points: Vec<Cartesian2D> = greville.iter().map(|&u| self.point(u)).collect();
+        let xs = least_squares(&rows, &points.iter().map(|q| q.x).collect::<Vec<f64>>())?;
+        let ys = least_squares(&rows, &points.iter().map(|q| q.y).collect::<Vec<f64>>())?;
+        BSpline::new(xs.into_iter().zip(ys).map(|(x, y)| Cartesian2D::new(x, y)).collect(), knots, p)
     }
+}
 
-    #[test]
-    fn test_fourth_order_roots() {
-        // polynomial: x^4 - 10x^3 + 35x^2 - 50x + 24 = (x - 1)(x - 2)(x - 3)(x - 4)
-        let poly = Polynomial::new(vec![1.0, -10.0, 35.0, -50.0, 24.0]);
-        let roots = poly.roots();
-        assert_eq!(roots.len(), 4);
-        let expected_roots = vec![
-            Complex::new(1.0, 0.0),
-            Complex::new(2.0, 0.0),
-            Complex::new(3.0, 0.0),
-            Complex::new(4.0, 0.0),
-        ];
-        for exp in expected_roots {
-            let found = roots.iter().any(|r| (r - exp).norm() < TOL);
-            assert!(found, "expected root {} not found", exp);
+// nurbs stuff //
+// rational b-splines, which give circles, arcs and ellipses exactly. a curve is kept as two
+// b-splines on the same knots: the weighted control points, and the weights themselves
+// (carried as the x of their control points), so evaluation and derivatives reuse de boor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nurbs {
+    weighted: BSpline,
+    weights: BSpline,
+}
+
+impl Nurbs {
+    pub fn new(control_points: Vec<Cartesian2D>, weights: Vec<f64>, knots: Vec<f64>, degree: usize) -> Result<Self, &'static str> {
+        if weights.len() != control_points.len() {
+            return Err("nurbs needs one weight per control point");
         }
-        println!("{:?}", poly.real_roots());
+        if weights.iter().any(|&w| w <= 0.0 || !w.is_finite()) {
+            return Err("nurbs weights must be positive");
+        }
+        let weighted = control_points.iter().zip(weights.iter()).map(|(p, w)| Cartesian2D::new(w * p.x, w * p.y)).collect();
+        let weights = weights.iter().map(|&w| Cartesian2D::new(w, 0.0)).collect();
+        Ok(Nurbs { weighted: BSpline::new(weighted, knots.clone(), degree)?, weights: BSpline::new(weights, knots, degree)? })
     }
-    
-    #[test]
-    fn test_tenth_order_real_roots() {
-        // polynomial: x^10 - 2.30x^9 + 3.45x^8 - 4.56x^7 + 1.23x^6 - 0.78x^5 + 0.56x^4 - 0.34x^3 + 0.12x^2 - 0.05x + 1.00
-        let poly = Polynomial::new(vec![
-            1.0,    // coefficient for x^10
-            -2.3,   // coefficient for x^9
-            3.45,   // coefficient for x^8
-            -4.56,  // coefficient for x^7
-            1.23,   // coefficient for x^6
-            -0.78,  // coefficient for x^5
-            0.56,   // coefficient for x^4
-            -0.34,  // coefficient for x^3
-            0.12,   // coefficient for x^2
-            -0.05,  // coefficient for x^1
-            1.0,    // constant term (x^0)
-        ]);
-        let real_roots = poly.real_roots();
-        println!("{:?}", real_roots);
-        // poly.plot().expect("erm");
+
+    // the same curve with every weight one
+    pub fn from_bspline(spline: &BSpline) -> Self {
+        let weights = vec![Cartesian2D::new(1.0, 0.0); spline.control_points.len()];
+        Nurbs { weighted: spline.clone(), weights: BSpline { control_points: weights, ..spline.clone() } }
+    }
+
+    // anticlockwise circle starting at angle zero
+    pub fn circle(centre: Cartesian2D, radius: f64) -> Result<Self, &'static str> {
+        Self::arc(centre, radius, 0.0, 2.0 * std::f64::consts::PI)
+    }
+
+    // circular arc from the start angle to the end one in radians, anticlockwise if the end
+    // is greater. made of quadratic pieces spanning at most a quarter turn each
+    pub fn arc(centre: Cartesian2D, radius: f64, start: f64, end: f64) -> Result<Self, &'static str> {
+        use std::f64::consts::{FRAC_PI_2, PI};
+        if radius <= 0.0 {
+            return Err("arc radius must be positive");
+        }
+        let sweep = end - start;
+        if sweep == 0.0 || sweep.abs() > 2.0 * PI * (1.0 + 1e-12) {
+            return Err("arc must sweep through more than nothing and at most a full turn");
+        }
+        let n_arcs = ((sweep.abs() / FRAC_PI_2) * (1.0 - 1e-12)).ceil().max(1.0) as usize;
+        let step = sweep / n_arcs as f64;
+        let on_circle = |angle: f64, r: f64| Cartesian2D::new(centre.x + r * angle.cos(), centre.y + r * angle.sin());
+
+        // each piece has its middle control point where the end tangents meet
+        let mut control_points = vec![on_circle(start, radius)];
+        let mut weights = vec![1.0];
+        let mut knots = vec![0.0; 3];
+        for k in 1..=n_arcs {
+            let angle = start + k as f64 * step;
+            control_points.push(on_circle(angle - 0.5 * step, radius / (0.5 * step).cos()));
+            control_points.push(on_circle(angle, radius));
+            weights.extend([(0.5 * step).cos(), 1.0]);
+            if k < n_arcs {
+                knots.extend([k as f64 / n_arcs as f64; 2]);
+            }
+        }
+        knots.extend([1.0; 3]);
+        if sweep.abs() >= 2.0 * PI {
+            control_points[2 * n_arcs] = control_points[0];
+        }
+        Self::new(control_points, weights, knots, 2)
+    }
+
+    // ellipse about the centre with the given semi-axes, the first turned anticlockwise from
+    // x by rotation radians. the unit circle stretched and turned, as nurbs are unchanged in
+    // form by affine maps
+    pub fn ellipse(centre: Cartesian2D, semi_major: f64, semi_minor: f64, rotation: f64) -> Result<Self, &'static str> {
+        if semi_major <= 0.0 || semi_minor <= 0.0 {
+            return Err("ellipse semi-axes must be positive");
+        }
+        let circle = Self::circle(Cartesian2D::new(0.0, 0.0), 1.0)?;
+        let (cos, sin) = (rotation.cos(), rotation.sin());
+        let control_points = circle
+            .control_points()
+            .iter()
+            .map(|p| {
+                let (x, y) = (semi_major * p.x, semi_minor * p.y);
+                Cartesian2D::new(centre.x + cos * x - sin * y, centre.y + sin * x + cos * y)
+            })
+            .collect();
+        Self::new(control_points, circle.weights(), circle.knots().to_vec(), 2)
+    }
+
+    pub fn control_points(&self) -> Vec<Cartesian2D> {
+        self.weighted
+            .control_points
+            .iter()
+            .zip(self.weights.control_points.iter())
+            .map(|(p, w)| Cartesian2D::new(p.x / w.x, p.y / w.x))
+            .collect()
+    }
+
+    pub fn weights(&self) -> Vec<f64> {
+        self.weights.control_points.iter().map(|w| w.x).collect()
+    }
+
+    pub fn knots(&self) -> &[f64] {
+        self.weighted.knots()
+    }
+
+    pub fn degree(&self) -> usize {
+        self.weighted.degree()
+    }
+
+    pub fn domain(&self) -> (f64, f64) {
+        self.weighted.domain()
+    }
+
+    pub fn point(&self, u: f64) -> Cartesian2D {
+        let (a, w) = (self.weighted.point(u), self.weights.point(u).x);
+        Cartesian2D::new(a.x / w, a.y / w)
+    }
+
+    // c = a / w, so c' = (a' - w' c) / w
+    pub fn first_derivative(&self, u: f64) -> Cartesian2D {
+        let (c, w) = (self.point(u), self.weights.point(u).x);
+        let (da, dw) = (self.weighted.first_derivative(u), self.weights.first_derivative(u).x);
+        Cartesian2D::new((da.x - dw * c.x) / w, (da.y - dw * c.y) / w)
+    }
+
+    // c'' = (a'' - 2 w' c' - w'' c) / w
+    pub fn second_derivative(&self, u: f64) -> Cartesian2D {
+        let (c, dc, w) = (self.point(u), self.first_derivative(u), self.weights.point(u).x);
+        let dw = self.weights.first_derivative(u).x;
+        let (d2a, d2w) = (self.weighted.second_derivative(u), self.weights.second_derivative(u).x);
+        Cartesian2D::new((d2a.x - 2.0 * dw * dc.x - d2w * c.x) / w, (d2a.y - 2.0 * dw * dc.y - d2w * c.y) / w)
+    }
+
+    // adds one knot without changing the curve, as for b-splines in homogeneous form
+    pub fn insert_knot(&mut self, u: f64) -> Result<(), &'static str> {
+        let mut weighted = self.weighted.clone();
+        weighted.insert_knot(u)?;
+        self.weights.insert_knot(u)?;
+        self.weighted = weighted;
+        Ok(())
+    }
+}
+
+impl ParametricCurve2D for BSpline {
+    fn domain(&self) -> (f64, f64) {
+        BSpline::domain(self)
+    }
+
+    fn point(&self, t: f64) -> Cartesian2D {
+        BSpline::point(self, t)
+    }
+
+    fn first_derivative(&self, t: f64) -> Cartesian2D {
+        BSpline::first_derivative(self, t)
+    }
+
+    fn second_derivative(&self, t: f64) -> Cartesian2D {
+        BSpline::second_derivative(self, t)
+    }
+
+    fn breakpoints(&self) -> Vec<f64> {
+        let (start, end) = BSpline::domain(self);
+        let mut knots: Vec<f64> = self.knots.iter().copied().filter(|&k| k >= start && k <= end).collect();
+        knots.dedup();
+        knots
+    }
+}
+
+impl ParametricCurve2D for Nurbs {
+    fn domain(&self) -> (f64, f64) {
+        Nurbs::domain(self)
+    }
+
+    fn point(&self, t: f64) -> Cartesian2D {
+        Nurbs::point(self, t)
+    }
+
+    fn first_derivative(&self, t: f64) -> Cartesian2D {
+        Nurbs::first_derivative(self, t)
+    }
+
+    fn second_derivative(&self, t: f64) -> Cartesian2D {
+        Nurbs::second_derivative(self, t)
+    }
+
+    fn breakpoints(&self) -> Vec<f64> {
+        ParametricCurve2D::breakpoints(&self.weighted)
+    }
+}
+
+// knot span holding u, the last non-empty one at the end of the domain
+fn find_span(knots: &[f64], degree: usize, n: usize, u: f64) -> usize {
+    if u >= knots[n + 1] {
+        return (degree..=n).rev().find(|&k| knots[k] < knots[k + 1]).unwrap_or(n);
+    }
+    if u <= knots[degree] {
+        return degree;
+    }
+    // largest k with knots[k] <= u
+    let k = knots[..=n + 1].partition_point(|&k| k <= u) - 1;
+    k.clamp(degree, n)
+}
+
+// the degree + 1 basis functions that are non-zero on the span, by cox de boor
+fn basis_functions(knots: &[f64], degree: usize, span: usize, u: f64) -> Vec<f64> {
+    let mut values = vec![0.0; degree + 1];
+    let (mut left, mut right) = (vec![0.0; degree + 1], vec![0.0; degree + 1]);
+    values[0] = 1.0;
+    for j in 1..=degree {
+        left[j] = u - knots[span + 1 - j];
+        right[j] = knots[span + j] - u;
+        let mut saved = 0.0;
+        for r in 0..j {
+            let denominator = right[r + 1] + left[j - r];
+            let temp = if denominator != 0.0 { values[r] / denominator } else { 0.0 };
+            values[r] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+        values[j] = saved;
+    }
+    values
+}
+
+// every basis function at u, for the n + 1 control points
+fn basis_row(knots: &[f64], degree: usize, n: usize, u: f64) -> Vec<f64> {
+    let span = find_span(knots, degree, n, u);
+    let mut row = vec![0.0; n + 1];
+    for (k, value) in basis_functions(knots, degree, span, u).into_iter().enumerate() {
+        row[span - degree + k] = value;
+    }
+    row
+}
+
+fn de_boor(control_points: &[Cartesian2D], knots: &[f64], degree: usize, u: f64) -> Cartesian2D {
+    let n = control_points.len() - 1;
+    let u = u.clamp(knots[degree], knots[n + 1]);
+    let k = find_span(knots, degree, n, u);
+    let mut d: Vec<Cartesian2D> = control_points[k - degree..=k].to_vec();
+    for r in 1..=degree {
+        for j in (r..=degree).rev() {
+            let i = j + k - degree;
+            let denominator = knots[i + degree + 1 - r] - knots[i];
+            let alpha = if denominator > 0.0 { (u - knots[i]) / denominator } else { 0.0 };
+            d[j] = Cartesian2D::new((1.0 - alpha) * d[j - 1].x + alpha * d[j].x, (1.0 - alpha) * d[j - 1].y + alpha * d[j].y);
+        }
+    }
+    d[degree]
+}
+
+// bezier stuff //
+// a single polynomial piece of any degree over t in [0, 1], given by its control points
+#[derive(Debug, Clone, PartialEq)]
+pub struct BezierCurve {
+    control_points: Vec<Cartesian2D>,
+}
+
+impl BezierCurve {
+    pub fn new(control_points: Vec<Cartesian2D>) -> Result<Self, &'static str> {
+        if control_points.is_empty() {
+            return Err("bezier curve needs at least one control point");
+        }
+        Ok(BezierCurve { control_points })
+    }
+
+    pub fn control_points(&self) -> &[Cartesian2D] {
+        &self.control_points
+    }
+
+    pub fn degree(&self) -> usize {
+        self.control_points.len() - 1
+    }
+
+    // every level of de casteljau's construction at t, from the control points down to the
+    // single point on the curve
+    fn de_casteljau(&self, t: f64) -> Vec<Vec<Cartesian2D>> {
+        let mut levels = vec![self.control_points.clone()];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .windows(2)
+                .map(|w| Cartesian2D::new((1.0 - t) * w[0].x + t * w[1].x, (1.0 - t) * w[0].y + t * w[1].y))
+                .collect();
+            levels.push(next);
+        }
+        levels
+    }
+
+    // the two halves either side of t, each a bezier curve of the same degree over [0, 1]
+    pub fn subdivide(&self, t: f64) -> Result<(BezierCurve, BezierCurve), &'static str> {
+        if !(0.0..=1.0).contains(&t) {
+            return Err("bezier curve can only be split inside [0, 1]");
+        }
+        let levels = self.de_casteljau(t);
+        let first = levels.iter().map(|level| level[0]).collect();
+        let second = levels.iter().rev().map(|level| level[level.len() - 1]).collect();
+        Ok((BezierCurve { control_points: first }, BezierCurve { control_points: second }))
+    }
+
+    // the same curve one degree higher
+    pub fn elevate_degree(&self) -> BezierCurve {
+        let n = self.control_points.len();
+        let p = &self.control_points;
+        let mut control_points = vec![p[0]];
+        for i in 1..n {
+            let a = i as f64 / n as f64;
+            control_points.push(Cartesian2D::new(a * p[i - 1].x + (1.0 - a) * p[i].x, a * p[i - 1].y + (1.0 - a) * p[i].y));
+        }
+        control_points.push(p[n - 1]);
+        BezierCurve { control_points }
+    }
+
+    // the derivative curve, one degree lower. a constant has a zero derivative
+    pub fn derivative(&self) -> BezierCurve {
+        if self.control_points.len() == 1 {
+            return BezierCurve { control_points: vec![Cartesian2D::new(0.0, 0.0)] };
+        }
+        let n = self.degree() as f64;
+        let control_points = self.control_points.windows(2).map(|w| Cartesian2D::new(n * (w[1].x - w[0].x), n * (w[1].y - w[0].y))).collect();
+        BezierCurve { control_points }
+    }
+
+    // one span with the knots clamped at 0 and 1
+    pub fn to_bspline(&self) -> BSpline {
+        let p = self.degree();
+        let knots = [vec![0.0; p + 1], vec![1.0; p + 1]].concat();
+        BSpline { control_points: self.control_points.clone(), knots, degree: p }
+    }
+
+    // one bezier curve for each knot span of a clamped b-spline, found by inserting every
+    // interior knot until it is as many times repeated as the degree
+    pub fn from_bspline(spline: &BSpline) -> Result<Vec<BezierCurve>, &'static str> {
+        let p = spline.degree();
+        let knots = spline.knots();
+        let ends = knots.len() - p - 1;
+        if knots[..=p].iter().any(|&k| k != knots[0]) || knots[ends..].iter().any(|&k| k != knots[knots.len() - 1]) {
+            return Err("b-spline must be clamped to split into bezier curves");
+        }
+        if p == 0 {
+            return Ok(spline.control_points().iter().map(|&q| BezierCurve { control_points: vec![q] }).collect());
+        }
+
+        let mut split = spline.clone();
+        let mut interior: Vec<f64> = knots[p + 1..ends].to_vec();
+        interior.dedup();
+        for &u in interior.iter() {
+            while split.knots.iter().filter(|&&k| k == u).count() < p {
+                split.insert_knot(u)?;
+            }
+        }
+        Ok(split.control_points.windows(p + 1).step_by(p).map(|w| BezierCurve { control_points: w.to_vec() }).collect())
+    }
+}
+
+impl ParametricCurve2D for BezierCurve {
+    fn domain(&self) -> (f64, f64) {
+        (0.0, 1.0)
+    }
+
+    // de casteljau, which stays stable at high degree
+    fn point(&self, t: f64) -> Cartesian2D {
+        let levels = self.de_casteljau(t);
+        levels[levels.len() - 1][0]
+    }
+
+    fn first_derivative(&self, t: f64) -> Cartesian2D {
+        self.derivative().point(t)
+    }
+
+    fn second_derivative(&self, t: f64) -> Cartesian2D {
+        self.derivative().derivative().point(t)
+    }
+}
+
+// cubic spline stuff //
+// what an interpolating cubic spline does at its ends. clamped ends take the direction the
+// curve leaves and arrives in. periodic splines need the last point back on the first and
+// join up smoothly there. not-a-knot makes the first two and last two pieces one cubic each
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndCondition {
+    Natural,
+    Clamped { start: Cartesian2D, end: Cartesian2D },
+    Periodic,
+    NotAKnot,
+}
+
+// a piecewise cubic through every point, kept as the points, their parameters and the
+// derivative of the curve at each. t runs from 0 at the first point to 1 at the last
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubicSpline {
+    points: Vec<Cartesian2D>,
+    parameters: Vec<f64>,
+    slopes: Vec<Cartesian2D>,
+}
+
+impl CubicSpline {
+    // twice continuously differentiable interpolation
+    pub fn new(points: Vec<Cartesian2D>, end: EndCondition, parameterisation: Parameterisation) -> Result<Self, &'static str> {
+        let (points, parameters) = Self::prepare(points, parameterisation, end == EndCondition::Periodic)?;
+        let n = points.len();
+        if n < 4 && matches!(end, EndCondition::Periodic | EndCondition::NotAKnot) {
+            return Err("periodic and not-a-knot splines need at least four points");
+        }
+        let h: Vec<f64> = parameters.windows(2).map(|w| w[1] - w[0]).collect();
+        let d: Vec<Vec2> = points.windows(2).zip(h.iter()).map(|(w, h)| [(w[1].x - w[0].x) / h, (w[1].y - w[0].y) / h]).collect();
+        let block = |a: f64| -> Mat2 { [[a, 0.0], [0.0, a]] };
+        let combine = |a: f64, u: &Vec2, b: f64, v: &Vec2| -> Vec2 { [a * u[0] + b * v[0], a * u[1] + b * v[1]] };
+        let scale = |a: f64, u: &Vec2| -> Vec2 { [a * u[0], a * u[1]] };
+
+        // continuity of the second derivative at each interior point, in terms of the slopes
+        let interior = |prev: usize, i: usize| -> (Mat2, Mat2, Mat2, Vec2) {
+            (block(h[i]), block(2.0 * (h[prev] + h[i])), block(h[prev]), combine(3.0 * h[i], &d[prev], 3.0 * h[prev], &d[i]))
+        };
+
+        let slopes = if end == EndCondition::Periodic {
+            let m = n - 1;
+            let (mut lower, mut diag, mut upper, mut rhs) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+            for i in 0..m {
+                let (l, c, u, r) = interior((i + m - 1) % m, i);
+                lower.push(l);
+                diag.push(c);
+                upper.push(u);
+                rhs.push(r);
+            }
+            let mut slopes = solve_periodic_block_tridiagonal(&lower, &diag, &upper, &rhs)?;
+            slopes.push(slopes[0]);
+            slopes
+        } else {
+            let (mut lower, mut diag, mut upper, mut rhs) = (vec![block(0.0)], vec![block(1.0)], vec![block(0.0)], vec![[0.0; 2]]);
+            for i in 1..n - 1 {
+                let (l, c, u, r) = interior(i - 1, i);
+                lower.push(l);
+                diag.push(c);
+                upper.push(u);
+                rhs.push(r);
+            }
+            lower.push(block(0.0));
+            diag.push(block(1.0));
+            upper.push(block(0.0));
+            rhs.push([0.0; 2]);
+
+            let last = n - 1;
+            match end {
+                EndCondition::Natural => {
+                    (diag[0], upper[0], rhs[0]) = (block(2.0), block(1.0), scale(3.0, &d[0]));
+                    (lower[last], diag[last], rhs[last]) = (block(1.0), block(2.0), scale(3.0, &d[last - 1]));
+                }
+                EndCondition::Clamped { start, end } => {
+                    let length: f64 = points.windows(2).map(|w| w[0].distance(&w[1])).sum();
+                    let speed = |v: Cartesian2D| -> Result<Vec2, &'static str> {
+                        let norm = v.x.hypot(v.y);
+                        if norm == 0.0 {
+                            return Err("clamped end directions must not be zero");
+                        }
+                        Ok([length * v.x / norm, length * v.y / norm])
+                    };
+                    rhs[0] = speed(start)?;
+                    rhs[last] = speed(end)?;
+                }
+                EndCondition::NotAKnot => {
+                    let (h0, h1) = (h[0], h[1]);
+                    (diag[0], upper[0]) = (block(h1), block(h0 + h1));
+                    rhs[0] = combine((h0 + 2.0 * (h0 + h1)) * h1 / (h0 + h1), &d[0], h0 * h0 / (h0 + h1), &d[1]);
+                    let (ha, hb) = (h[last - 2], h[last - 1]);
+                    (lower[last], diag[last]) = (block(ha + hb), block(ha));
+                    rhs[last] = combine(hb * hb / (ha + hb), &d[last - 2], (2.0 * (ha + hb) + hb) * ha / (ha + hb), &d[last - 1]);
+                }
+                EndCondition::Periodic => unreachable!(),
+            }
+            solve_block_tridiagonal(&lower, &diag, &upper, &rhs)?
+        };
+        Ok(CubicSpline { points, parameters, slopes: slopes.into_iter().map(|m| Cartesian2D::new(m[0], m[1])).collect() })
+    }
+
+    // akima's local interpolation, once differentiable. each slope comes from the four
+    // chords around the point, weighted away from the side that turns sharply, so it does
+    // not overshoot steps in the data. a closed set of points wraps round
+    pub fn akima(points: Vec<Cartesian2D>, parameterisation: Parameterisation) -> Result<Self, &'static str> {
+        let closed = points.len() > 2 && points[0] == points[points.len() - 1];
+        let (points, parameters) = Self::prepare(points, parameterisation, closed)?;
+        let n = points.len();
+        let chords: Vec<Cartesian2D> = points
+            .windows(2)
+            .zip(parameters.windows(2))
+            .map(|(p, t)| {
+                let h = t[1] - t[0];
+                Cartesian2D::new((p[1].x - p[0].x) / h, (p[1].y - p[0].y) / h)
+            })
+            .collect();
+
+        // two chords more at each end, wrapped round or extrapolated
+        let m = chords.len();
+        let extrapolate = |a: Cartesian2D, b: Cartesian2D| Cartesian2D::new(2.0 * a.x - b.x, 2.0 * a.y - b.y);
+        let chord = |k: isize| -> Cartesian2D {
+            if closed {
+                return chords[k.rem_euclid(m as isize) as usize];
+            }
+            let (first, second) = (chords[0], chords[1.min(m - 1)]);
+            let (last, before) = (chords[m - 1], chords[m.saturating_sub(2)]);
+            match k {
+                -2 => extrapolate(extrapolate(first, second), first),
+                -1 => extrapolate(first, second),
+                k if k as usize == m => extrapolate(last, before),
+                k if k as usize == m + 1 => extrapolate(extrapolate(last, before), last),
+                k => chords[k as usize],
+            }
+        };
+
+        let slopes = (0..n as isize)
+            .map(|i| {
+                let (a, b, c, d) = (chord(i - 2), chord(i - 1), chord(i), chord(i + 1));
+                let w1 = (d.x - c.x).hypot(d.y - c.y);
+                let w2 = (b.x - a.x).hypot(b.y - a.y);
+                if w1 + w2 == 0.0 {
+                    Cartesian2D::new(0.5 * (b.x + c.x), 0.5 * (b.y + c.y))
+                } else {
+                    Cartesian2D::new((w1 * b.x + w2 * c.x) / (w1 + w2), (w1 * b.y + w2 * c.y) / (w1 + w2))
+                }
+            })
+            .collect();
+        Ok(CubicSpline { points, parameters, slopes })
+    }
+
+    // checks the points and gives their parameters, snapping a closed curve shut
+    fn prepare(mut points: Vec<Cartesian2D>, parameterisation: Parameterisation, closed: bool) -> Result<(Vec<Cartesian2D>, Vec<f64>), &'static str> {
+        if points.len() < 2 {
+            return Err("cubic spline needs at least two points");
+        }
+        if points.windows(2).any(|w| w[0] == w[1]) {
+            return Err("cubic spline points must not repeat one after another");
+        }
+        if closed {
+            let n = points.len();
+            let length: f64 = points.windows(2).map(|w| w[0].distance(&w[1])).sum();
+            if points[0].distance(&points[n - 1]) > Self::CLOSURE_TOL * length {
+                return Err("periodic spline must end at its first point");
+            }
+            points[n - 1] = points[0];
+        }
+        let parameters = parameterisation.parameters(&points)?;
+        Ok((points, parameters))
+    }
+
+    // relative to the length of the polygon through the points
+    const CLOSURE_TOL: f64 = 1e-10;
+
+    pub fn points(&self) -> &[Cartesian2D] {
+        &self.points
+    }
+
+    pub fn parameters(&self) -> &[f64] {
+        &self.parameters
+    }
+
+    // piece holding t, its length and how far along it t is
+    fn locate(&self, t: f64) -> (usize, f64, f64) {
+        let t = t.clamp(0.0, 1.0);
+        let k = (self.parameters.partition_point(|&p| p <= t).max(1) - 1).min(self.points.len() - 2);
+        let h = self.parameters[k + 1] - self.parameters[k];
+        (k, h, (t - self.parameters[k]) / h)
+    }
+
+    // hermite form on a piece, with the basis functions or their derivatives in s given by
+    // the weights on the two points and two slopes
+    fn hermite(&self, k: usize, weights: [f64; 4]) -> Cartesian2D {
+        let (p0, p1, m0, m1) = (self.points[k], self.points[k + 1], self.slopes[k], self.slopes[k + 1]);
+        Cartesian2D::new(
+            weights[0] * p0.x + weights[1] * m0.x + weights[2] * p1.x + weights[3] * m1.x,
+            weights[0] * p0.y + weights[1] * m0.y + weights[2] * p1.y + weights[3] * m1.y,
+        )
+    }
+}
+
+impl ParametricCurve2D for CubicSpline {
+    fn domain(&self) -> (f64, f64) {
+        (0.0, 1.0)
+    }
+
+    fn point(&self, t: f64) -> Cartesian2D {
+        let (k, h, s) = self.locate(t);
+        let (s2, s3) = (s * s, s * s * s);
+        self.hermite(k, [2.0 * s3 - 3.0 * s2 + 1.0, h * (s3 - 2.0 * s2 + s), 3.0 * s2 - 2.0 * s3, h * (s3 - s2)])
+    }
+
+    fn first_derivative(&self, t: f64) -> Cartesian2D {
+        let (k, h, s) = self.locate(t);
+        let s2 = s * s;
+        self.hermite(k, [(6.0 * s2 - 6.0 * s) / h, 3.0 * s2 - 4.0 * s + 1.0, (6.0 * s - 6.0 * s2) / h, 3.0 * s2 - 2.0 * s])
+    }
+
+    fn second_derivative(&self, t: f64) -> Cartesian2D {
+        let (k, h, s) = self.locate(t);
+        self.hermite(k, [(12.0 * s - 6.0) / (h * h), (6.0 * s - 4.0) / h, (6.0 - 12.0 * s) / (h * h), (6.0 * s - 2.0) / h])
+    }
+
+    fn breakpoints(&self) -> Vec<f64> {
+        self.parameters.clone()
+    }
+}
+
+// intersection stuff //
+// where two curves cross or touch, with the parameter on each
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection {
+    point: Cartesian2D,
+    first: f64,
+    second: f64,
+}
+
+impl Intersection {
+    pub fn point(&self) -> Cartesian2D {
+        self.point
+    }
+
+    // parameter on the first curve
+    pub fn first(&self) -> f64 {
+        self.first
+    }
+
+    // parameter on the second curve
+    pub fn second(&self) -> f64 {
+        self.second
+    }
+}
+
+// every point where two curves meet, in order along the first. each curve is flattened
+// into short chords, ranges of chords whose bounding boxes overlap are halved until single
+// chords are left, and each such pair is refined by newton iteration on the curves
+// themselves. points come out to round off, touching ones included as long as the two
+// curves bend by different amounts there. curves that run along each other for a stretch
+// do not have isolated intersections, and only where the overlap starts or ends may be found
+pub fn intersect(a: &(impl ParametricCurve2D + ?Sized), b: &(impl ParametricCurve2D + ?Sized)) -> Vec<Intersection> {
+    let (chords_a, chords_b) = (Chords::new(a), Chords::new(b));
+    let scale = chords_a.size.max(chords_b.size);
+    let margin = chords_a.flatness + chords_b.flatness;
+
+    let mut found: Vec<Intersection> = Vec::new();
+    let mut stack = vec![(0, chords_a.len(), 0, chords_b.len())];
+    while let Some((a0, a1, b0, b1)) = stack.pop() {
+        let (box_a, box_b) = (chords_a.bounds(a0, a1), chords_b.bounds(b0, b1));
+        if box_a.0.x > box_b.1.x + margin || box_b.0.x > box_a.1.x + margin || box_a.0.y > box_b.1.y + margin || box_b.0.y > box_a.1.y + margin {
+            continue;
+        }
+        if a1 - a0 > 1 {
+            let mid = (a0 + a1) / 2;
+            stack.extend([(a0, mid, b0, b1), (mid, a1, b0, b1)]);
+        } else if b1 - b0 > 1 {
+            let mid = (b0 + b1) / 2;
+            stack.extend([(a0, a1, b0, mid), (a0, a1, mid, b1)]);
+        } else if let Some(hit) = refine(a, b, chords_a.guess(a0, &chords_b, b0), scale) {
+            if !found.iter().any(|f| f.point.distance(&hit.point) <= MERGE_TOL * scale) {
+                found.push(hit);
+            }
+        }
+    }
+    found.sort_by(|x, y| x.first.total_cmp(&y.first));
+    found
+}
+
+// where a curve meets the infinite line through point along direction. the second
+// parameter of each intersection is how many directions along the line it is from point
+pub fn intersect_line(curve: &(impl ParametricCurve2D + ?Sized), point: Cartesian2D, direction: Cartesian2D) -> Result<Vec<Intersection>, &'static str> {
+    let length = direction.x.hypot(direction.y);
+    if length == 0.0 {
+        return Err("line direction must not be zero");
+    }
+    // the part of the line that can reach the curve, from its bounding box
+    let chords = Chords::new(curve);
+    let (lo, hi) = chords.bounds(0, chords.len());
+    let corners = [lo, hi, Cartesian2D::new(lo.x, hi.y), Cartesian2D::new(hi.x, lo.y)];
+    let along = |p: &Cartesian2D| ((p.x - point.x) * direction.x + (p.y - point.y) * direction.y) / (length * length);
+    let pad = (chords.size + chords.flatness) / length;
+    let start = corners.iter().map(along).fold(f64::INFINITY, f64::min) - pad;
+    let end = corners.iter().map(along).fold(f64::NEG_INFINITY, f64::max) + pad;
+
+    let at = |s: f64| Cartesian2D::new(point.x + s * direction.x, point.y + s * direction.y);
+    let segment = Segment2D::new(at(start), at(end));
+    Ok(intersect(curve, &segment)
+        .into_iter()
+        .map(|hit| Intersection { second: start + hit.second * (end - start), ..hit })
+        .collect())
+}
+
+// how close a point must be to both curves, relative to their size
+const INTERSECTION_TOL: f64 = 1e-9;
+// newton carries on towards this, as where curves only touch it converges slowly
+const NEWTON_TOL: f64 = 1e-14;
+// sine of the angle between tangents below which curves are taken to touch rather than cross
+const TANGENT_TOL: f64 = 1e-4;
+// intersections closer than this, relative to the size of the curves, are the same one
+const MERGE_TOL: f64 = 1e-6;
+// how far chords may stray from their curve, relative to its size
+const FLATNESS: f64 = 1e-3;
+// chords per smooth piece before any are split for flatness, so loops are not missed
+const MIN_CHORDS: usize = 16;
+const MAX_FLATTEN_DEPTH: usize = 12;
+const MAX_NEWTON_STEPS: usize = 60;
+
+// a curve flattened into chords between increasing parameters
+struct Chords {
+    parameters: Vec<f64>,
+    points: Vec<Cartesian2D>,
+    size: f64,
+    flatness: f64,
+}
+
+impl Chords {
+    fn new(curve: &(impl ParametricCurve2D + ?Sized)) -> Self {
+        let breakpoints = curve.breakpoints();
+        let mut parameters: Vec<f64> = breakpoints
+            .windows(2)
+            .flat_map(|w| (0..MIN_CHORDS).map(move |k| w[0] + (w[1] - w[0]) * k as f64 / MIN_CHORDS as f64))
+            .collect();
+        parameters.push(breakpoints[breakpoints.len() - 1]);
+        let points: Vec<Cartesian2D> = parameters.iter().map(|&t| curve.point(t)).collect();
+        let mut chords = Chords { parameters, points, size: 0.0, flatness: 0.0 };
+        let (lo, hi) = chords.bounds(0, chords.len());
+        chords.size = lo.distance(&hi).max(f64::MIN_POSITIVE);
+        chords.flatness = FLATNESS * chords.size;
+
+        // split chords until their middles lie close to them
+        let (parameters, points) = (std::mem::take(&mut chords.parameters), std::mem::take(&mut chords.points));
+        chords.parameters.push(parameters[0]);
+        chords.points.push(points[0]);
+        for k in 0..parameters.len() - 1 {
+            chords.split(curve, (parameters[k], points[k]), (parameters[k + 1], points[k + 1]), MAX_FLATTEN_DEPTH);
+        }
+        chords
+    }
+
+    // adds the far end of a chord, with any points needed in between first
+    fn split(&mut self, curve: &(impl ParametricCurve2D + ?Sized), start: (f64, Cartesian2D), end: (f64, Cartesian2D), depth: usize) {
+        let t = 0.5 * (start.0 + end.0);
+        let middle = curve.point(t);
+        if depth > 0 && distance_to_segment(&middle, &start.1, &end.1) > self.flatness {
+            self.split(curve, start, (t, middle), depth - 1);
+            self.split(curve, (t, middle), end, depth - 1);
+        } else {
+            self.parameters.push(end.0);
+            self.points.push(end.1);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.points.len() - 1
+    }
+
+    // box round chords lo to hi - 1
+    fn bounds(&self, lo: usize, hi: usize) -> (Cartesian2D, Cartesian2D) {
+        self.points[lo..=hi].iter().fold(
+            (Cartesian2D::new(f64::INFINITY, f64::INFINITY), Cartesian2D::new(f64::NEG_INFINITY, f64::NEG_INFINITY)),
+            |(lo, hi), p| (Cartesian2D::new(lo.x.min(p.x), lo.y.min(p.y)), Cartesian2D::new(hi.x.max(p.x), hi.y.max(p.y))),
+        )
+    }
+
+    // parameters to start newton from for two chords: where they cross, or their middles if
+    // they miss, which is how curves that only touch are caught
+    fn guess(&self, k: usize, other: &Chords, l: usize) -> (f64, f64) {
+        let (p, q) = (self.points[k], other.points[l]);
+        let r = Cartesian2D::new(self.points[k + 1].x - p.x, self.points[k + 1].y - p.y);
+        let w = Cartesian2D::new(other.points[l + 1].x - q.x, other.points[l + 1].y - q.y);
+        let denominator = cross(&r, &w);
+        let (s, u) = if denominator != 0.0 {
+            let pq = Cartesian2D::new(q.x - p.x, q.y - p.y);
+            (cross(&pq, &w) / denominator, cross(&pq, &r) / denominator)
+        } else {
+            (0.5, 0.5)
+        };
+        let (s, u) = if (0.0..=1.0).contains(&s) && (0.0..=1.0).contains(&u) { (s, u) } else { (0.5, 0.5) };
+        let lerp = |parameters: &[f64], k: usize, f: f64| parameters[k] + f * (parameters[k + 1] - parameters[k]);
+        (lerp(&self.parameters, k, s), lerp(&other.parameters, l, u))
+    }
+}
+
+// newton iteration on a(s) = b(t) from a starting guess, kept inside both domains. it runs
+// on until the gap or the step is down to round off, then keeps the point if the gap is small
+fn refine(a: &(impl ParametricCurve2D + ?Sized), b: &(impl ParametricCurve2D + ?Sized), guess: (f64, f64), scale: f64) -> Option<Intersection> {
+    let ((a0, a1), (b0, b1)) = (a.domain(), b.domain());
+    let gap = |s: f64, t: f64| -> Vec2 {
+        let (p, q) = (a.point(s), b.point(t));
+        [p.x - q.x, p.y - q.y]
+    };
+    let (mut s, mut t) = guess;
+    for _ in 0..MAX_NEWTON_STEPS {
+        let f = gap(s, t);
+        if f[0].hypot(f[1]) <= NEWTON_TOL * scale {
+            break;
+        }
+        let (da, db) = (a.first_derivative(s), b.first_derivative(t));
+        let Ok(inverse) = mat2_inverse(&[[da.x, -db.x], [da.y, -db.y]]) else { break };
+        let step = mat2_vec(&inverse, &f);
+        let (next_s, next_t) = ((s - step[0]).clamp(a0, a1), (t - step[1]).clamp(b0, b1));
+        let stalled = (next_s - s).abs() <= NEWTON_TOL * (a1 - a0) && (next_t - t).abs() <= NEWTON_TOL * (b1 - b0);
+        (s, t) = (next_s, next_t);
+        if stalled {
+            break;
+        }
+    }
+    let f = gap(s, t);
+    let hit = (f[0].hypot(f[1]) <= INTERSECTION_TOL * scale).then(|| Intersection { point: a.point(s), first: s, second: t });
+    hit.map(|hit| polish_contact(a, b, hit, scale))
+}
+
+// where the curves only touch the gap shrinks with the square of the distance from the
+// contact, so newton driving the gap down to round off still leaves the point about the
+// square root of round off away. the contact is better found where the tangents are
+// parallel and the gap has no part along them, which newton solves well as long as the
+// curvatures differ. the polished point is only kept if the curves still meet there, so
+// near tangent crossings are left alone
+fn polish_contact(a: &(impl ParametricCurve2D + ?Sized), b: &(impl ParametricCurve2D + ?Sized), hit: Intersection, scale: f64) -> Intersection {
+    let ((a0, a1), (b0, b1)) = (a.domain(), b.domain());
+    let (da, db) = (a.first_derivative(hit.first), b.first_derivative(hit.second));
+    let lengths = da.x.hypot(da.y) * db.x.hypot(db.y);
+    if lengths == 0.0 || cross(&da, &db).abs() > TANGENT_TOL * lengths {
+        return hit;
+    }
+    let (mut s, mut t) = (hit.first, hit.second);
+    for _ in 0..MAX_NEWTON_STEPS {
+        let (p, q) = (a.point(s), b.point(t));
+        let f = Cartesian2D::new(p.x - q.x, p.y - q.y);
+        let (da, db) = (a.first_derivative(s), b.first_derivative(t));
+        let (dda, ddb) = (a.second_derivative(s), b.second_derivative(t));
+        let residual = [dot(&f, &da), cross(&da, &db)];
+        let jacobian = [[dot(&da, &da) + dot(&f, &dda), -dot(&da, &db)], [cross(&dda, &db), cross(&da, &ddb)]];
+        let Ok(inverse) = mat2_inverse(&jacobian) else { return hit };
+        let step = mat2_vec(&inverse, &residual);
+        let (next_s, next_t) = ((s - step[0]).clamp(a0, a1), (t - step[1]).clamp(b0, b1));
+        let stalled = (next_s - s).abs() <= NEWTON_TOL * (a1 - a0) && (next_t - t).abs() <= NEWTON_TOL * (b1 - b0);
+        (s, t) = (next_s, next_t);
+        if stalled {
+            break;
+        }
+    }
+    let (p, q) = (a.point(s), b.point(t));
+    if p.distance(&q) <= INTERSECTION_TOL * scale { Intersection { point: p, first: s, second: t } } else { hit }
+}
+
+fn distance_to_segment(p: &Cartesian2D, a: &Cartesian2D, b: &Cartesian2D) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length2 = dx * dx + dy * dy;
+    if length2 == 0.0 {
+        return p.distance(a);
+    }
+    let f = (((p.x - a.x) * dx + (p.y - a.y) * dy) / length2).clamp(0.0, 1.0);
+    p.distance(&Cartesian2D::new(a.x + f * dx, a.y + f * dy))
+}
+
+// airfoil stuff //
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingEdge {
+    Sharp,
+    Blunt,
+}
+
+// a closed airfoil curve in selig order: from the trailing edge over the upper surface to
+// the leading edge and back under the lower surface, ending where it started. the chord runs
+// from the leading edge at the origin to the trailing edge (or the middle of a blunt one) at
+// (1, 0). a blunt trailing edge is closed by a straight segment across its base
+#[derive(Debug, Clone)]
+pub struct Airfoil {
+    name: String,
+    points: Vec<Cartesian2D>,
+    trailing_edge: TrailingEdge,
+    leading_edge: usize,
+}
+
+impl Airfoil {
+    // trailing edge gaps smaller than this fraction of the chord count as sharp
+    const SHARP_TE_TOL: f64 = 1e-6;
+    // points closer than this fraction of the chord count as duplicates
+    const DUPLICATE_TOL: f64 = 1e-9;
+
+    // points run round the section once from the trailing edge, in either direction, with
+    // the last point back at the trailing edge (sharp) or on the other side of it (blunt)
+    pub fn new(name: &str, points: Vec<Cartesian2D>) -> Result<Self, &'static str> {
+        if points.len() < 5 {
+            return Err("airfoil must have at least five points");
+        }
+
+        let (first, last) = (points[0], points[points.len() - 1]);
+        let te = Cartesian2D::new(0.5 * (first.x + last.x), 0.5 * (first.y + last.y));
+        let (leading_edge, le) = points
+            .iter()
+            .enumerate()
+            .max_by(|a, b| te.distance(a.1).total_cmp(&te.distance(b.1)))
+            .map(|(k, p)| (k, *p))
+            .ok_or("airfoil has no points")?;
+        let chord = te.distance(&le);
+        if chord <= 0.0 {
+            return Err("airfoil has zero chord");
+        }
+
+        // leading edge to the origin, chord along x with unit length
+        let (cos, sin) = ((te.x - le.x) / chord, (te.y - le.y) / chord);
+        let mut points: Vec<Cartesian2D> = points
+            .iter()
+            .map(|p| {
+                let (dx, dy) = (p.x - le.x, p.y - le.y);
+                Cartesian2D::new((cos * dx + sin * dy) / chord, (-sin * dx + cos * dy) / chord)
+            })
+            .collect();
+
+        // work on the open loop of distinct points, closed again at the end
+        let n = points.len();
+        let trailing_edge = if points[0].distance(&points[n - 1]) <= Self::SHARP_TE_TOL {
+            points.pop();
+            TrailingEdge::Sharp
+        } else {
+            TrailingEdge::Blunt
+        };
+        if find_duplicate(&points, Self::DUPLICATE_TOL).is_some() {
+            return Err("airfoil has duplicated points");
+        }
+
+        // upper surface first, which runs the loop anticlockwise
+        let m = points.len();
+        let mut leading_edge = leading_edge;
+        let area: f64 = (0..m).map(|k| cross(&points[k], &points[(k + 1) % m])).sum();
+        if area < 0.0 {
+            match trailing_edge {
+                TrailingEdge::Sharp => {
+                    points[1..].reverse();
+                    leading_edge = (m - leading_edge) % m;
+                }
+                TrailingEdge::Blunt => {
+                    points.reverse();
+                    leading_edge = m - 1 - leading_edge;
+                }
+            }
+        }
+        points.push(points[0]);
+
+        Ok(Airfoil { name: name.trim().to_string(), points, trailing_edge, leading_edge })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // closed curve, the last point repeats the first
+    pub fn points(&self) -> &[Cartesian2D] {
+        &self.points
+    }
+
+    pub fn trailing_edge(&self) -> TrailingEdge {
+        self.trailing_edge
+    }
+
+    // gap across the trailing edge as a fraction of the chord
+    pub fn trailing_edge_thickness(&self) -> f64 {
+        match self.trailing_edge {
+            TrailingEdge::Sharp => 0.0,
+            TrailingEdge::Blunt => self.points[0].distance(&self.points[self.points.len() - 2]),
+        }
+    }
+
+    pub fn leading_edge_index(&self) -> usize {
+        self.leading_edge
+    }
+
+    // trailing edge to leading edge
+    pub fn upper(&self) -> &[Cartesian2D] {
+        &self.points[..=self.leading_edge]
+    }
+
+    // leading edge to trailing edge, without the closing segment of a blunt trailing edge
+    pub fn lower(&self) -> &[Cartesian2D] {
+        match self.trailing_edge {
+            TrailingEdge::Sharp => &self.points[self.leading_edge..],
+            TrailingEdge::Blunt => &self.points[self.leading_edge..self.points.len() - 1],
+        }
+    }
+
+    // from surfaces already at unit chord that both run from a shared leading edge at the
+    // origin to the trailing edge
+    fn from_surfaces(name: &str, upper: Vec<Cartesian2D>, lower: Vec<Cartesian2D>) -> Self {
+        let leading_edge = upper.len() - 1;
+        let (upper_te, lower_te) = (upper[leading_edge], lower[lower.len() - 1]);
+        let mut points: Vec<Cartesian2D> = upper.into_iter().rev().chain(lower.into_iter().skip(1)).collect();
+        let trailing_edge = if upper_te.distance(&lower_te) <= Self::SHARP_TE_TOL {
+            points.pop();
+            TrailingEdge::Sharp
+        } else {
+            TrailingEdge::Blunt
+        };
+        points.push(points[0]);
+        Airfoil { name: name.to_string(), points, trailing_edge, leading_edge }
+    }
+}
+
+// naca stuff //
+// analytic naca 4 and 5 digit sections, standard and reflexed. 6-series thickness forms come
+// from a conformal mapping and are only published as tables, so they are not generated here
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NacaCamber {
+    // max camber m at chord position p
+    FourDigit { m: f64, p: f64 },
+    // the 5 digit mean lines, scaled from the tabulated design lift of 0.3
+    FiveDigit { r: f64, k1: f64, scale: f64 },
+    FiveDigitReflex { r: f64, k1: f64, k21: f64, scale: f64 },
+}
+
+impl NacaCamber {
+    // height and slope of the mean line at x
+    fn at(&self, x: f64) -> (f64, f64) {
+        match *self {
+            NacaCamber::FourDigit { m: 0.0, .. } => (0.0, 0.0),
+            NacaCamber::FourDigit { m, p } if x < p => {
+                (m / (p * p) * (2.0 * p * x - x * x), 2.0 * m / (p * p) * (p - x))
+            }
+            NacaCamber::FourDigit { m, p } => {
+                let q = (1.0 - p) * (1.0 - p);
+                (m / q * (1.0 - 2.0 * p + 2.0 * p * x - x * x), 2.0 * m / q * (p - x))
+            }
+            NacaCamber::FiveDigit { r, k1, scale } if x < r => (
+                scale * k1 / 6.0 * (x * x * x - 3.0 * r * x * x + r * r * (3.0 - r) * x),
+                scale * k1 / 6.0 * (3.0 * x * x - 6.0 * r * x + r * r * (3.0 - r)),
+            ),
+            NacaCamber::FiveDigit { r, k1, scale } => {
+                (scale * k1 * r * r * r / 6.0 * (1.0 - x), -scale * k1 * r * r * r / 6.0)
+            }
+            NacaCamber::FiveDigitReflex { r, k1, k21, scale } => {
+                let a = if x < r { 1.0 } else { k21 };
+                let c = k21 * (1.0 - r).powi(3) + r * r * r;
+                (
+                    scale * k1 / 6.0 * (a * (x - r).powi(3) - c * x + r * r * r),
+                    scale * k1 / 6.0 * (3.0 * a * (x - r).powi(2) - c),
+                )
+            }
+        }
+    }
+}
+
+// builds an airfoil from a naca designation such as "0012", "NACA 2412" or "23012", with
+// n points on each surface from the leading edge to the trailing edge. points are cosine
+// clustered towards both edges unless set otherwise. the trailing edge is left open as the
+// sections are defined, or can be closed, which the c-grid generator needs
+#[derive(Debug, Clone)]
+pub struct NacaAirfoil {
+    designation: String,
+    camber: NacaCamber,
+    thickness: f64,
+    n: usize,
+    closed_trailing_edge: bool,
+    cosine_spacing: bool,
+}
+
+impl NacaAirfoil {
+    // 5 digit mean line constants by the position digit, for standard and reflexed lines
+    const FIVE_DIGIT: [(f64, f64); 5] = [(0.0580, 361.4), (0.1260, 51.64), (0.2025, 15.957), (0.2900, 6.643), (0.3910, 3.230)];
+    // 0.3180 is a tabulated r, not 1/pi
+    #[allow(clippy::approx_constant)]
+    const FIVE_DIGIT_REFLEX: [(f64, f64, f64); 4] =
+        [(0.1300, 51.99, 0.000764), (0.2170, 15.793, 0.00677), (0.3180, 6.520, 0.0303), (0.4410, 3.191, 0.1355)];
+
+    pub fn new(designation: &str, n: usize) -> Result<Self, &'static str> {
+        if n < 3 {
+            return Err("naca airfoil needs at least three points per surface");
+        }
+        let code: String = designation
+            .trim()
+            .trim_start_matches(|c: char| c.is_ascii_alphabetic() || c.is_whitespace())
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        // 6-series designations carry a dash, an A or a bracketed subscript, e.g. 63-412, 64A010
+        if code.starts_with('6') && code.contains(['-', 'A', 'a', '(']) {
+            return Err("naca 6-series sections are tabulated, only 4 and 5 digit sections are generated");
+        }
+        if !code.chars().all(|c| c.is_ascii_digit()) {
+            return Err("naca designation must be 4 or 5 digits");
+        }
+        let digits: Vec<usize> = code.bytes().map(|b| (b - b'0') as usize).collect();
+
+        let camber = match digits[..] {
+            [m, p, _, _] => {
+                if (m == 0) != (p == 0) {
+                    return Err("naca 4 digit camber and its position must both be zero or both be set");
+                }
+                NacaCamber::FourDigit { m: m as f64 / 100.0, p: p as f64 / 10.0 }
+            }
+            [l, p, 0, _, _] if (1..=5).contains(&p) => {
+                let (r, k1) = Self::FIVE_DIGIT[p - 1];
+                NacaCamber::FiveDigit { r, k1, scale: l as f64 / 2.0 }
+            }
+            [l, p, 1, _, _] if (2..=5).contains(&p) => {
+                let (r, k1, k21) = Self::FIVE_DIGIT_REFLEX[p - 2];
+                NacaCamber::FiveDigitReflex { r, k1, k21, scale: l as f64 / 2.0 }
+            }
+            [_, _, _, _, _] => return Err("naca 5 digit mean line is not one of the standard ones"),
+            _ => return Err("naca designation must be 4 or 5 digits"),
+        };
+        let thickness = (10 * digits[digits.len() - 2] + digits[digits.len() - 1]) as f64 / 100.0;
+        if thickness <= 0.0 {
+            return Err("naca airfoil must have some thickness");
+        }
+
+        Ok(NacaAirfoil {
+            designation: format!("NACA {}", code),
+            camber,
+            thickness,
+            n,
+            closed_trailing_edge: false,
+            cosine_spacing: true,
+        })
+    }
+
+    pub fn set_closed_trailing_edge(&mut self, closed: bool) {
+        self.closed_trailing_edge = closed;
+    }
+
+    pub fn set_cosine_spacing(&mut self, cosine: bool) {
+        self.cosine_spacing = cosine;
+    }
+
+    pub fn thickness(&self) -> f64 {
+        self.thickness
+    }
+
+    // half thickness at x, with the last coefficient changed to close the trailing edge
+    fn half_thickness(&self, x: f64) -> f64 {
+        let a4 = if self.closed_trailing_edge { 0.1036 } else { 0.1015 };
+        5.0 * self.thickness * (0.2969 * x.sqrt() - 0.1260 * x - 0.3516 * x * x + 0.2843 * x.powi(3) - a4 * x.powi(4))
+    }
+
+    // the section already has its leading edge at the origin and unit chord along x, so the
+    // points go straight into an airfoil without being normalised again
+    pub fn generate(&self) -> Airfoil {
+        let surface = |side: f64| -> Vec<Cartesian2D> {
+            chord_stations(self.n, self.cosine_spacing)
+                .into_iter()
+                .map(|x| {
+                    let (yc, slope) = self.camber.at(x);
+                    let theta = slope.atan();
+                    let yt = side * self.half_thickness(x);
+                    Cartesian2D::new(x - yt * theta.sin(), yc + yt * theta.cos())
+                })
+                .collect()
+        };
+        Airfoil::from_surfaces(&self.designation, surface(1.0), surface(-1.0))
+    }
+}
+
+// cst stuff //
+// kulfan's class shape transformation. each surface is y = c(x) s(x) + x dz, where the class
+// function c = sqrt(x) (1 - x) gives a round nose and a sharp tail, s is a bernstein
+// polynomial whose weights are the design variables, and dz lifts the end of the surface to
+// give a blunt trailing edge
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstAirfoil {
+    upper: Vec<f64>,
+    lower: Vec<f64>,
+    upper_trailing_edge: f64,
+    lower_trailing_edge: f64,
+}
+
+impl CstAirfoil {
+    // bernstein weights for each surface, lower ones usually negative. the surfaces may use
+    // different orders
+    pub fn new(upper: Vec<f64>, lower: Vec<f64>) -> Result<Self, &'static str> {
+        if upper.is_empty() || lower.is_empty() {
+            return Err("cst airfoil needs at least one weight per surface");
+        }
+        Ok(CstAirfoil { upper, lower, upper_trailing_edge: 0.0, lower_trailing_edge: 0.0 })
+    }
+
+    // least squares fit of weights up to this bernstein order to each surface of an airfoil,
+    // keeping its trailing edge
+    pub fn fit(airfoil: &Airfoil, order: usize) -> Result<Self, &'static str> {
+        let upper_trailing_edge = airfoil.upper()[0].y;
+        let lower_trailing_edge = airfoil.lower()[airfoil.lower().len() - 1].y;
+        let fit_surface = |surface: &[Cartesian2D], dz: f64| -> Result<Vec<f64>, &'static str> {
+            let (rows, rhs): (Vec<Vec<f64>>, Vec<f64>) = surface
+                .iter()
+                .map(|p| {
+                    let x = p.x.clamp(0.0, 1.0);
+                    (cst_basis(order, x), p.y - x * dz)
+                })
+                .unzip();
+            least_squares(&rows, &rhs).map_err(|_| "too few points on the airfoil surface for this cst order")
+        };
+        Ok(CstAirfoil {
+            upper: fit_surface(airfoil.upper(), upper_trailing_edge)?,
+            lower: fit_surface(airfoil.lower(), lower_trailing_edge)?,
+            upper_trailing_edge,
+            lower_trailing_edge,
+        })
+    }
+
+    // heights of the upper and lower surfaces where they end, upper above lower
+    pub fn set_trailing_edge(&mut self, upper: f64, lower: f64) -> Result<(), &'static str> {
+        if upper < lower {
+            return Err("upper trailing edge must not be below the lower one");
+        }
+        self.upper_trailing_edge = upper;
+        self.lower_trailing_edge = lower;
+        Ok(())
+    }
+
+    pub fn upper_weights(&self) -> &[f64] {
+        &self.upper
+    }
+
+    pub fn lower_weights(&self) -> &[f64] {
+        &self.lower
+    }
+
+    // heights of the upper and lower surfaces at x
+    pub fn at(&self, x: f64) -> (f64, f64) {
+        let height = |weights: &[f64], dz: f64| -> f64 {
+            let basis = cst_basis(weights.len() - 1, x);
+            weights.iter().zip(basis).map(|(w, b)| w * b).sum::<f64>() + x * dz
+        };
+        (height(&self.upper, self.upper_trailing_edge), height(&self.lower, self.lower_trailing_edge))
+    }
+
+    // n cosine clustered points on each surface
+    pub fn generate(&self, n: usize) -> Result<Airfoil, &'static str> {
+        if n < 3 {
+            return Err("cst airfoil needs at least three points per surface");
+        }
+        let stations = chord_stations(n, true);
+        let (upper, lower): (Vec<Cartesian2D>, Vec<Cartesian2D>) = stations
+            .into_iter()
+            .map(|x| {
+                let (upper, lower) = self.at(x);
+                (Cartesian2D::new(x, upper), Cartesian2D::new(x, lower))
+            })
+            .unzip();
+        Ok(Airfoil::from_surfaces("CST", upper, lower))
+    }
+}
+
+// class function times each bernstein polynomial of this order at x
+fn cst_basis(order: usize, x: f64) -> Vec<f64> {
+    let class = x.sqrt() * (1.0 - x);
+    let mut binomial = 1.0;
+    (0..=order)
+        .map(|i| {
+            if i > 0 {
+                binomial *= (order + 1 - i) as f64 / i as f64;
+            }
+            class * binomial * x.powi(i as i32) * (1.0 - x).powi((order - i) as i32)
+        })
+        .collect()
+}
+
+// hicks henne stuff //
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    Upper,
+    Lower,
+}
+
+// a smooth bump sin(pi x^e)^width on one surface, peaking at x = peak and vanishing at both
+// edges, so that a sum of them perturbs a baseline shape without touching its nose or tail
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HicksHenneBump {
+    surface: Surface,
+    amplitude: f64,
+    peak: f64,
+    width: f64,
+}
+
+impl HicksHenneBump {
+    // positive amplitudes move the surface up, whichever surface it is
+    pub fn new(surface: Surface, amplitude: f64, peak: f64, width: f64) -> Result<Self, &'static str> {
+        if !(peak > 0.0 && peak < 1.0) {
+            return Err("hicks henne bump must peak inside the chord");
+        }
+        if width <= 0.0 {
+            return Err("hicks henne bump width must be positive");
+        }
+        Ok(HicksHenneBump { surface, amplitude, peak, width })
+    }
+
+    pub fn surface(&self) -> Surface {
+        self.surface
+    }
+
+    // displacement at x along the chord
+    pub fn at(&self, x: f64) -> f64 {
+        let exponent = 0.5_f64.ln() / self.peak.ln();
+        let x = x.clamp(0.0, 1.0);
+        self.amplitude * (std::f64::consts::PI * x.powf(exponent)).sin().abs().powf(self.width)
+    }
+}
+
+impl Airfoil {
+    // a copy with the bumps added to the surfaces they are on
+    pub fn with_bumps(&self, bumps: &[HicksHenneBump]) -> Airfoil {
+        let mut airfoil = self.clone();
+        let n = airfoil.points.len();
+        for (k, p) in airfoil.points[..n - 1].iter_mut().enumerate() {
+            let surface = if k <= self.leading_edge { Surface::Upper } else { Surface::Lower };
+            p.y += bumps.iter().filter(|b| b.surface == surface).map(|b| b.at(p.x)).sum::<f64>();
+        }
+        airfoil.points[n - 1] = airfoil.points[0];
+        airfoil
+    }
+}
+
+// n stations from the leading edge to the trailing edge, cosine clustered towards both
+fn chord_stations(n: usize, cosine: bool) -> Vec<f64> {
+    (0..n)
+        .map(|k| {
+            let s = k as f64 / (n - 1) as f64;
+            if cosine { 0.5 * (1.0 - (std::f64::consts::PI * s).cos()) } else { s }
+        })
+        .collect()
+}
+
+fn cross(a: &Cartesian2D, b: &Cartesian2D) -> f64 {
+    a.x * b.y - b.x * a.y
+}
+
+fn dot(a: &Cartesian2D, b: &Cartesian2D) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+// any two points closer than tol, found by sweeping the points sorted by x
+fn find_duplicate(points: &[Cartesian2D], tol: f64) -> Option<(usize, usize)> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| points[a].x.total_cmp(&points[b].x));
+    for (k, &a) in order.iter().enumerate() {
+        for &b in order[k + 1..].iter() {
+            if points[b].x - points[a].x > tol {
+                break;
+            }
+            if points[a].distance(&points[b]) <= tol {
+                return Some((a.min(b), a.max(b)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_complex::Complex;
+
+    const TOL: f64 = 1e-6;
+
+    fn poly_near_zero(poly: &Polynomial, root: Complex<f64>) -> bool {
+        let f_val = (poly.eqn())(root.re);
+        f_val.abs() < TOL
+    }
+
+    #[test]
+    fn test_quadratic_roots() {
+        // polynomial: x^2 - 5x + 6 = (x - 2)(x - 3)
+        let poly = Polynomial::new(vec![1.0, -5.0, 6.0]);
+        let roots = poly.roots();
+        assert_eq!(roots.len(), 2);
+        let expected_roots = vec![
+            Complex::new(2.0, 0.0),
+            Complex::new(3.0, 0.0),
+        ];
+        for exp in expected_roots {
+            let found = roots.iter().any(|r| (r - exp).norm() < TOL);
+            assert!(found, "expected root {} not found", exp);
+        }
+    }
+
+    #[test]
+    fn test_cubic_roots() {
+        // polynomial: x^3 - 6x^2 + 11x - 6 = (x - 1)(x - 2)(x - 3)
+        let poly = Polynomial::new(vec![1.0, -6.0, 11.0, -6.0]);
+        let roots = poly.roots();
+        assert_eq!(roots.len(), 3);
+        let expected_roots = vec![
+            Complex::new(1.0, 0.0),
+            Complex::new(2.0, 0.0),
+            Complex::new(3.0, 0.0),
+        ];
+        for exp in expected_roots {
+            let found = roots.iter().any(|r| (r - exp).norm() < TOL);
+            assert!(found, "expected root {} not found", exp);
+        }
+    }
+
+    #[test]
+    fn test_fourth_order_roots() {
+        // polynomial: x^4 - 10x^3 + 35x^2 - 50x + 24 = (x - 1)(x - 2)(x - 3)(x - 4)
+        let poly = Polynomial::new(vec![1.0, -10.0, 35.0, -50.0, 24.0]);
+        let roots = poly.roots();
+        assert_eq!(roots.len(), 4);
+        let expected_roots = vec![
+            Complex::new(1.0, 0.0),
+            Complex::new(2.0, 0.0),
+            Complex::new(3.0, 0.0),
+            Complex::new(4.0, 0.0),
+        ];
+        for exp in expected_roots {
+            let found = roots.iter().any(|r| (r - exp).norm() < TOL);
+            assert!(found, "expected root {} not found", exp);
+        }
+        println!("{:?}", poly.real_roots());
+    }
+    
+    #[test]
+    fn test_tenth_order_real_roots() {
+        // polynomial: x^10 - 2.30x^9 + 3.45x^8 - 4.56x^7 + 1.23x^6 - 0.78x^5 + 0.56x^4 - 0.34x^3 + 0.12x^2 - 0.05x + 1.00
+        let poly = Polynomial::new(vec![
+            1.0,    // coefficient for x^10
+            -2.3,   // coefficient for x^9
+            3.45,   // coefficient for x^8
+            -4.56,  // coefficient for x^7
+            1.23,   // coefficient for x^6
+            -0.78,  // coefficient for x^5
+            0.56,   // coefficient for x^4
+            -0.34,  // coefficient for x^3
+            0.12,   // coefficient for x^2
+            -0.05,  // coefficient for x^1
+            1.0,    // constant term (x^0)
+        ]);
+        let real_roots = poly.real_roots();
+        println!("{:?}", real_roots);
+        // poly.plot().expect("erm");
+    }
+
+    #[test]
+    fn test_naca_four_digit() {
+        let airfoil = NacaAirfoil::new("NACA 0012", 101).unwrap().generate();
+        assert_eq!(airfoil.name(), "NACA 0012");
+        assert_eq!(airfoil.trailing_edge(), TrailingEdge::Blunt);
+        assert_eq!(airfoil.points().len(), 202);
+        assert_eq!(airfoil.points()[100], Cartesian2D::new(0.0, 0.0));
+        assert!((airfoil.trailing_edge_thickness() - 0.00252).abs() < 1e-5);
+        let max = airfoil.upper().iter().map(|p| p.y()).fold(0.0, f64::max);
+        assert!((max - 0.06).abs() < 1e-4);
+
+        let mut naca = NacaAirfoil::new("2412", 51).unwrap();
+        naca.set_closed_trailing_edge(true);
+        naca.set_cosine_spacing(false);
+        let airfoil = naca.generate();
+        assert_eq!(airfoil.trailing_edge(), TrailingEdge::Sharp);
+        assert_eq!(airfoil.points().len(), 101);
+        assert_eq!(airfoil.points()[0], airfoil.points()[100]);
+        // mean line peaks at 2% chord, 40% along
+        let (yc, slope) = NacaCamber::FourDigit { m: 0.02, p: 0.4 }.at(0.4);
+        assert!((yc - 0.02).abs() < 1e-12 && slope.abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_naca_five_digit() {
+        // the 230 mean line has its maximum camber 15% along the chord
+        let naca = NacaAirfoil::new("23012", 3).unwrap();
+        let (_, slope) = naca.camber.at(0.1508);
+        assert!(slope.abs() < 1e-3);
+        assert!(naca.camber.at(1.0).0.abs() < 1e-12);
+
+        // reflexed lines with camber far enough back turn up towards the trailing edge
+        let naca = NacaAirfoil::new("25112", 3).unwrap();
+        assert!(naca.camber.at(0.0).0.abs() < 1e-12 && naca.camber.at(1.0).0.abs() < 1e-12);
+        assert!(naca.camber.at(1.0).1 > 0.0);
+        assert!((naca.thickness() - 0.12).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_naca_rejects_bad_designations() {
+        assert!(NacaAirfoil::new("6412", 11).is_ok());
+        assert!(NacaAirfoil::new("63-412", 11).is_err());
+        assert!(NacaAirfoil::new("64A010", 11).is_err());
+        assert!(NacaAirfoil::new("2012", 11).is_err());
+        assert!(NacaAirfoil::new("0000", 11).is_err());
+        assert!(NacaAirfoil::new("23712", 11).is_err());
+        assert!(NacaAirfoil::new("12", 11).is_err());
+        assert!(NacaAirfoil::new("0012", 2).is_err());
+    }
+
+    #[test]
+    fn test_cst_round_trip() {
+        let mut cst = CstAirfoil::new(vec![0.17, 0.16, 0.15, 0.2], vec![-0.15, -0.1, -0.08]).unwrap();
+        cst.set_trailing_edge(0.002, -0.001).unwrap();
+        let airfoil = cst.generate(41).unwrap();
+        assert_eq!(airfoil.trailing_edge(), TrailingEdge::Blunt);
+        assert_eq!(airfoil.points()[0].y(), 0.002);
+
+        let refit = CstAirfoil::fit(&airfoil, 3).unwrap();
+        for (a, b) in refit.upper_weights().iter().zip(cst.upper_weights()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+        let lower = CstAirfoil::fit(&airfoil, 2).unwrap();
+        for (a, b) in lower.lower_weights().iter().zip(cst.lower_weights()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+        assert!(CstAirfoil::fit(&cst.generate(4).unwrap(), 6).is_err());
+    }
+
+    #[test]
+    fn test_cst_fits_naca() {
+        let mut naca = NacaAirfoil::new("2412", 81).unwrap();
+        naca.set_closed_trailing_edge(true);
+        let airfoil = naca.generate();
+        let cst = CstAirfoil::fit(&airfoil, 8).unwrap();
+        let fitted = cst.generate(81).unwrap();
+        assert_eq!(fitted.trailing_edge(), TrailingEdge::Sharp);
+        // the nose is nearly vertical, so compare heights away from it
+        for p in airfoil.points().iter().filter(|p| p.x() > 0.01) {
+            let (upper, lower) = cst.at(p.x());
+            assert!((p.y() - upper).abs().min((p.y() - lower).abs()) < 5e-4);
+        }
+    }
+
+    #[test]
+    fn test_hicks_henne_bumps() {
+        let bump = HicksHenneBump::new(Surface::Upper, 0.01, 0.3, 2.0).unwrap();
+        assert!((bump.at(0.3) - 0.01).abs() < 1e-12);
+        assert_eq!((bump.at(0.0), bump.at(1.0).abs() < 1e-12), (0.0, true));
+        assert!(HicksHenneBump::new(Surface::Lower, 0.01, 1.0, 2.0).is_err());
+
+        let mut naca = NacaAirfoil::new("0012", 41).unwrap();
+        naca.set_closed_trailing_edge(true);
+        let baseline = naca.generate();
+        let lower = HicksHenneBump::new(Surface::Lower, -0.005, 0.6, 3.0).unwrap();
+        let bumped = baseline.with_bumps(&[bump, lower]);
+        assert_eq!(bumped.trailing_edge(), TrailingEdge::Sharp);
+        assert_eq!(bumped.points()[0], bumped.points()[bumped.points().len() - 1]);
+        for (a, b) in baseline.upper().iter().zip(bumped.upper()) {
+            assert!((b.y() - a.y() - bump.at(a.x())).abs() < 1e-15);
+        }
+        for (a, b) in baseline.lower().iter().zip(bumped.lower()).skip(1) {
+            assert!(b.y() <= a.y());
+        }
+    }
+
+    fn cubic_spline() -> BSpline {
+        let control_points = [(0.0, 0.0), (1.0, 2.0), (2.0, -1.0), (3.0, 1.5), (4.0, 0.5), (5.0, 2.0)];
+        BSpline::new(
+            control_points.iter().map(|&(x, y)| Cartesian2D::new(x, y)).collect(),
+            vec![0.0, 0.0, 0.0, 0.0, 0.3, 0.5, 1.0, 1.0, 1.0, 1.0],
+            3,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bspline_evaluation_and_derivatives() {
+        // a single quadratic span is a bezier curve
+        let points = vec![Cartesian2D::new(0.0, 0.0), Cartesian2D::new(1.0, 2.0), Cartesian2D::new(2.0, 0.0)];
+        let quadratic = BSpline::new(points, vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0], 2).unwrap();
+        assert!(quadratic.point(0.5).distance(&Cartesian2D::new(1.0, 1.0)) < 1e-12);
+
+        let spline = cubic_spline();
+        assert_eq!(spline.point(0.0), Cartesian2D::new(0.0, 0.0));
+        assert!(spline.point(1.0).distance(&Cartesian2D::new(5.0, 2.0)) < 1e-12);
+        let h = 1e-5;
+        for u in [0.1, 0.3, 0.42, 0.77] {
+            let (a, b) = (spline.point(u - h), spline.point(u + h));
+            let d1 = spline.first_derivative(u);
+            assert!((d1.x() - (b.x() - a.x()) / (2.0 * h)).abs() < 1e-6);
+            assert!((d1.y() - (b.y() - a.y()) / (2.0 * h)).abs() < 1e-6);
+            // the third derivative jumps at knots, so this is only first order there
+            let (a, b) = (spline.first_derivative(u - h), spline.first_derivative(u + h));
+            let d2 = spline.second_derivative(u);
+            assert!((d2.y() - (b.y() - a.y()) / (2.0 * h)).abs() < 1e-4 * d2.y().abs().max(1.0));
+        }
+        assert_eq!(spline.derivative().derivative().derivative().derivative().point(0.4), Cartesian2D::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_bspline_knot_insertion_and_degree_elevation() {
+        let spline = cubic_spline();
+        let mut inserted = spline.clone();
+        inserted.insert_knot(0.3).unwrap();
+        inserted.insert_knot(0.8).unwrap();
+        inserted.insert_knot(0.3).unwrap();
+        assert!(inserted.insert_knot(0.3).is_err());
+        assert!(inserted.insert_knot(1.0).is_err());
+        assert_eq!(inserted.control_points().len(), 9);
+
+        let elevated = spline.elevate_degree().unwrap();
+        assert_eq!(elevated.degree(), 4);
+        assert_eq!(elevated.knots(), [0.0, 0.0, 0.0, 0.0, 0.0, 0.3, 0.3, 0.5, 0.5, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        for k in 0..=50 {
+            let u = k as f64 / 50.0;
+            assert!(inserted.point(u).distance(&spline.point(u)) < 1e-12);
+            assert!(elevated.point(u).distance(&spline.point(u)) < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_bspline_lsq() {
+        let data: Vec<Cartesian2D> = (0..60)
+            .map(|k| {
+                let x = k as f64 / 59.0 * std::f64::consts::PI;
+                Cartesian2D::new(x, x.sin() + if k % 2 == 0 { 1e-4 } else { -1e-4 })
+            })
+            .collect();
+        for parameterisation in [Parameterisation::ChordLength, Parameterisation::Centripetal] {
+            let spline = BSpline::lsq(data.clone(), 3, 10, parameterisation).unwrap();
+            assert_eq!((spline.control_points().len(), spline.knots().len()), (10, 14));
+            assert_eq!(spline.point(0.0), data[0]);
+            assert_eq!(spline.point(1.0), data[59]);
+            let parameters = parameterisation.parameters(&data).unwrap();
+            for (q, &u) in data.iter().zip(parameters.iter()) {
+                assert!(spline.point(u).distance(q) < 1e-3);
+            }
+        }
+
+        // as many control points as data points interpolates
+        let few = data.iter().step_by(10).copied().collect::<Vec<Cartesian2D>>();
+        let spline = BSpline::lsq(few.clone(), 3, few.len(), Parameterisation::ChordLength).unwrap();
+        let parameters = Parameterisation::ChordLength.parameters(&few).unwrap();
+        for (q, &u) in few.iter().zip(parameters.iter()) {
+            assert!(spline.point(u).distance(q) < 1e-10);
+        }
+
+        assert!(BSpline::lsq(few.clone(), 3, 7, Parameterisation::ChordLength).is_err());
+        assert!(BSpline::lsq(few.clone(), 3, 3, Parameterisation::ChordLength).is_err());
+        assert!(BSpline::lsq(vec![Cartesian2D::new(1.0, 1.0); 5], 2, 3, Parameterisation::ChordLength).is_err());
+    }
+
+    #[test]
+    fn test_nurbs_circle_is_exact() {
+        let centre = Cartesian2D::new(1.0, -2.0);
+        let circle = Nurbs::circle(centre, 0.5).unwrap();
+        assert_eq!((circle.control_points().len(), circle.degree()), (9, 2));
+        assert_eq!(circle.point(0.0), circle.point(1.0));
+        for k in 0..=100 {
+            let u = k as f64 / 100.0;
+            let (p, d1, d2) = (circle.point(u), circle.first_derivative(u), circle.second_derivative(u));
+            assert!((p.distance(&centre) - 0.5).abs() < 1e-14);
+            // tangent square to the radius, and the curvature of a circle
+            let (rx, ry) = (p.x() - centre.x(), p.y() - centre.y());
+            assert!((rx * d1.x() + ry * d1.y()).abs() < 1e-12);
+            let speed = d1.x().hypot(d1.y());
+            let curvature = (d1.x() * d2.y() - d1.y() * d2.x()) / speed.powi(3);
+            assert!((curvature - 2.0).abs() < 1e-10);
+        }
+
+        let h = 1e-6;
+        let (a, b) = (circle.point(0.4 - h), circle.point(0.4 + h));
+        assert!((circle.first_derivative(0.4).y() - (b.y() - a.y()) / (2.0 * h)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_nurbs_arcs_and_ellipse() {
+        use std::f64::consts::PI;
+        let origin = Cartesian2D::new(0.0, 0.0);
+        let arc = Nurbs::arc(origin, 2.0, 0.25 * PI, -0.5 * PI).unwrap();
+        assert_eq!(arc.control_points().len(), 5);
+        assert!(arc.point(0.0).distance(&Cartesian2D::new(2.0_f64.sqrt(), 2.0_f64.sqrt())) < 1e-14);
+        assert!(arc.point(1.0).distance(&Cartesian2D::new(0.0, -2.0)) < 1e-14);
+        assert!((arc.point(0.37).distance(&origin) - 2.0).abs() < 1e-14);
+        // clockwise, so the tangent at the start points down and right
+        assert!(arc.first_derivative(0.0).y() < 0.0);
+        assert_eq!(Nurbs::arc(origin, 1.0, 0.0, 0.1).unwrap().control_points().len(), 3);
+        assert!(Nurbs::arc(origin, 1.0, 0.0, 0.0).is_err());
+        assert!(Nurbs::arc(origin, 1.0, 0.0, 7.0).is_err());
+
+        let ellipse = Nurbs::ellipse(Cartesian2D::new(1.0, 1.0), 3.0, 1.0, 0.5 * PI).unwrap();
+        for k in 0..=40 {
+            let p = ellipse.point(k as f64 / 40.0);
+            let (x, y) = (p.y() - 1.0, 1.0 - p.x());
+            assert!((x * x / 9.0 + y * y - 1.0).abs() < 1e-13);
+        }
+
+        let mut refined = Nurbs::circle(origin, 1.0).unwrap();
+        refined.insert_knot(0.6).unwrap();
+        assert!((refined.point(0.63).distance(&origin) - 1.0).abs() < 1e-14);
+        let spline = Nurbs::from_bspline(&cubic_spline());
+        assert!(spline.point(0.42).distance(&cubic_spline().point(0.42)) < 1e-14);
+    }
+
+    #[test]
+    fn test_parametric_segment_and_polyline() {
+        // vertical, which y = f(x) could not describe
+        let segment = Segment2D::new(Cartesian2D::new(1.0, 0.0), Cartesian2D::new(1.0, 3.0));
+        assert_eq!(segment.point(0.5), Cartesian2D::new(1.0, 1.5));
+        assert_eq!(segment.tangent(0.2), Cartesian2D::new(0.0, 1.0));
+        assert_eq!(segment.normal(0.2), Cartesian2D::new(-1.0, 0.0));
+        assert_eq!((segment.curvature(0.7), segment.arc_length()), (0.0, 3.0));
+
+        let square = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0)];
+        let polyline = Polyline2D::new(square.iter().map(|&(x, y)| Cartesian2D::new(x, y)).collect()).unwrap();
+        assert!(polyline.is_closed());
+        assert_eq!(polyline.arc_length(), 4.0);
+        assert_eq!(polyline.point(0.375), Cartesian2D::new(1.0, 0.5));
+        assert_eq!(polyline.point(1.0), Cartesian2D::new(0.0, 0.0));
+        assert_eq!(polyline.tangent(0.6), Cartesian2D::new(-1.0, 0.0));
+        // anticlockwise, so the normal points inside
+        assert_eq!(polyline.normal(0.6), Cartesian2D::new(0.0, -1.0));
+        assert!((polyline.arc_length_between(0.1, 0.35) - 1.0).abs() < 1e-15);
+        assert!(Polyline2D::new(vec![Cartesian2D::new(1.0, 1.0); 3]).is_err());
+    }
+
+    #[test]
+    fn test_polyline_with_repeated_points() {
+        // closing point written twice, as airfoil files sometimes do, and a repeat part way
+        let points = [(0.0, 0.0), (1.0, 0.0), (1.0, 0.0), (1.0, 2.0), (1.0, 2.0)];
+        let polyline = Polyline2D::new(points.iter().map(|&(x, y)| Cartesian2D::new(x, y)).collect()).unwrap();
+        assert_eq!(polyline.first_derivative(1.0), Cartesian2D::new(0.0, 3.0));
+        assert_eq!(polyline.tangent(1.0), Cartesian2D::new(0.0, 1.0));
+        assert_eq!(polyline.point(1.0), Cartesian2D::new(1.0, 2.0));
+        assert_eq!(polyline.tangent(0.0), Cartesian2D::new(1.0, 0.0));
+        assert_eq!(polyline.tangent(1.0 / 3.0), Cartesian2D::new(0.0, 1.0));
+        assert_eq!(polyline.point(1.0 / 3.0), Cartesian2D::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn test_parametric_polynomial_and_splines() {
+        // y = x^2 from 0 to 1
+        let parabola = PolynomialCurve::new(Polynomial::new(vec![1.0, 0.0, 0.0]), 0.0, 1.0).unwrap();
+        assert_eq!(parabola.point(0.5), Cartesian2D::new(0.5, 0.25));
+        assert!((parabola.curvature(0.0) - 2.0).abs() < 1e-15);
+        let exact = 0.5 * 5.0_f64.sqrt() + 0.25 * (2.0 + 5.0_f64.sqrt()).ln();
+        assert!((parabola.arc_length() - exact).abs() < 1e-12);
+        assert!(PolynomialCurve::new(Polynomial::new(vec![1.0]), 1.0, 1.0).is_err());
+
+        let circle = Nurbs::circle(Cartesian2D::new(0.0, 0.0), 2.0).unwrap();
+        assert!((circle.arc_length() - 4.0 * std::f64::consts::PI).abs() < 1e-10);
+        assert!((circle.curvature(0.3) - 0.5).abs() < 1e-12);
+        let n = circle.normal(0.3);
+        let p = circle.point(0.3);
+        assert!((n.x() + 0.5 * p.x()).abs() < 1e-12 && (n.y() + 0.5 * p.y()).abs() < 1e-12);
+
+        let spline = cubic_spline();
+        let curve: &dyn ParametricCurve2D = &spline;
+        assert_eq!(curve.domain(), (0.0, 1.0));
+        let whole = curve.arc_length();
+        assert!((curve.arc_length_between(0.0, 0.4) + curve.arc_length_between(0.4, 1.0) - whole).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_arc_length_inversion() {
+        let parabola = PolynomialCurve::new(Polynomial::new(vec![1.0, 0.0, 0.0]), 0.0, 1.0).unwrap();
+        let length = parabola.arc_length();
+        let t = parabola.parameter_at_length(0.3 * length).unwrap();
+        assert!((parabola.arc_length_between(0.0, t) - 0.3 * length).abs() < 1e-12);
+        assert_eq!(parabola.parameter_at_length(length).unwrap(), 1.0);
+        assert_eq!(parabola.parameter_at_length(0.0).unwrap(), 0.0);
+        assert!(parabola.parameter_at_length(1.01 * length).is_err());
+    }
+
+    #[test]
+    fn test_curvature_distribution() {
+        use std::f64::consts::PI;
+        // on a circle curvature is the same everywhere, so spacing stays even
+        let circle = Nurbs::arc(Cartesian2D::new(0.0, 0.0), 1.0, 0.0, PI).unwrap();
+        let points = circle.distribute_by_curvature(7, 2.0).unwrap();
+        let chord = 2.0 * (PI / 12.0).sin();
+        for w in points.windows(2) {
+            assert!((w[0].distance(&w[1]) - chord).abs() < 1e-10);
+        }
+
+        // an ellipse bends most at the ends of its long axis
+        let ellipse = Nurbs::ellipse(Cartesian2D::new(0.0, 0.0), 3.0, 1.0, 0.0).unwrap();
+        let even = ellipse.distribute_by_curvature(41, 0.0).unwrap();
+        let bent = ellipse.distribute_by_curvature(41, 1.0).unwrap();
+        // even by arc length, so the chords differ only by how much they bend
+        assert!((even[0].distance(&even[1]) / even[10].distance(&even[11]) - 1.0).abs() < 0.05);
+        assert!(bent[0].distance(&bent[1]) < 0.5 * bent[10].distance(&bent[11]));
+        assert!(bent[40].distance(&bent[0]) < 1e-12);
+        assert!(ellipse.distribute_by_curvature(1, 1.0).is_err());
+    }
+
+    fn wavy(n: usize) -> Vec<Cartesian2D> {
+        (0..n).map(|k| Cartesian2D::new(k as f64 + 0.3 * (k % 2) as f64, (1.3 * k as f64).sin())).collect()
+    }
+
+    #[test]
+    fn test_cubic_spline_end_conditions() {
+        let points = wavy(8);
+        let clamped = EndCondition::Clamped { start: Cartesian2D::new(0.0, 1.0), end: Cartesian2D::new(1.0, -1.0) };
+        for end in [EndCondition::Natural, clamped, EndCondition::NotAKnot] {
+            let spline = CubicSpline::new(points.clone(), end, Parameterisation::ChordLength).unwrap();
+            for (p, &t) in points.iter().zip(spline.parameters()) {
+                assert!(spline.point(t).distance(p) < 1e-12);
+            }
+            // second derivative is continuous at every interior point
+            for &t in &spline.parameters()[1..7] {
+                let (a, b) = (spline.second_derivative(t - 1e-9), spline.second_derivative(t + 1e-9));
+                assert!(a.distance(&b) < 1e-5 * a.x().hypot(a.y()).max(1.0));
+            }
+            match end {
+                EndCondition::Natural => {
+                    assert!(spline.second_derivative(0.0).x().hypot(spline.second_derivative(0.0).y()) < 1e-9);
+                    assert!(spline.second_derivative(1.0).x().hypot(spline.second_derivative(1.0).y()) < 1e-9);
+                }
+                EndCondition::Clamped { .. } => {
+                    assert!(spline.tangent(0.0).distance(&Cartesian2D::new(0.0, 1.0)) < 1e-12);
+                    let s = 0.5_f64.sqrt();
+                    assert!(spline.tangent(1.0).distance(&Cartesian2D::new(s, -s)) < 1e-12);
+                }
+                _ => {
+                    // the second derivative is linear across the first two pieces
+                    let t = spline.parameters();
+                    let slope = |a: f64, b: f64| {
+                        let (da, db) = (spline.second_derivative(a), spline.second_derivative(b));
+                        (db.y() - da.y()) / (b - a)
+                    };
+                    let (first, second) = (slope(t[0], t[1] - 1e-9), slope(t[1] + 1e-9, t[2]));
+                    assert!((first - second).abs() < 1e-6 * first.abs().max(1.0));
+                }
+            }
+        }
+        assert!(CubicSpline::new(points[..3].to_vec(), EndCondition::NotAKnot, Parameterisation::ChordLength).is_err());
+        let mut repeated = points.clone();
+        repeated.insert(3, points[3]);
+        assert!(CubicSpline::new(repeated, EndCondition::Natural, Parameterisation::ChordLength).is_err());
+    }
+
+    #[test]
+    fn test_periodic_cubic_spline() {
+        let mut points: Vec<Cartesian2D> = (0..12)
+            .map(|k| {
+                let theta = 2.0 * std::f64::consts::PI * k as f64 / 12.0;
+                Cartesian2D::new(2.0 * theta.cos(), theta.sin())
+            })
+            .collect();
+        points.push(Cartesian2D::new(2.0, 1e-14));
+        let spline = CubicSpline::new(points.clone(), EndCondition::Periodic, Parameterisation::Centripetal).unwrap();
+        assert_eq!(spline.points()[12], spline.points()[0]);
+        assert!(spline.first_derivative(0.0).distance(&spline.first_derivative(1.0)) < 1e-10);
+        assert!(spline.second_derivative(0.0).distance(&spline.second_derivative(1.0)) < 1e-9);
+        assert!((spline.point(0.5).x() + 2.0).abs() < 1e-12);
+
+        points[12] = Cartesian2D::new(2.0, 0.1);
+        assert!(CubicSpline::new(points, EndCondition::Periodic, Parameterisation::ChordLength).is_err());
+    }
+
+    #[test]
+    fn test_akima_does_not_overshoot() {
+        let step: Vec<Cartesian2D> = (0..8).map(|k| Cartesian2D::new(k as f64, if k < 4 { 0.0 } else { 1.0 })).collect();
+        let akima = CubicSpline::akima(step.clone(), Parameterisation::ChordLength).unwrap();
+        let natural = CubicSpline::new(step.clone(), EndCondition::Natural, Parameterisation::ChordLength).unwrap();
+        let range = |spline: &CubicSpline| {
+            (0..=400).map(|k| spline.point(k as f64 / 400.0).y()).fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), y| (lo.min(y), hi.max(y)))
+        };
+        let (lo, hi) = range(&akima);
+        assert!(lo >= -1e-12 && hi <= 1.0 + 1e-12);
+        let (lo, hi) = range(&natural);
+        assert!(lo < -0.01 && hi > 1.01);
+        for (p, &t) in step.iter().zip(akima.parameters()) {
+            assert!(akima.point(t).distance(p) < 1e-12);
+        }
+
+        // closed points wrap round, so the join is smooth
+        let mut closed: Vec<Cartesian2D> = wavy(6);
+        closed.push(closed[0]);
+        let akima = CubicSpline::akima(closed, Parameterisation::ChordLength).unwrap();
+        assert!(akima.first_derivative(0.0).distance(&akima.first_derivative(1.0)) < 1e-10);
+    }
+
+    fn bezier() -> BezierCurve {
+        let control_points = [(0.0, 0.0), (1.0, 3.0), (3.0, 3.0), (4.0, -1.0), (6.0, 1.0)];
+        BezierCurve::new(control_points.iter().map(|&(x, y)| Cartesian2D::new(x, y)).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_bezier_evaluation_and_derivative() {
+        let curve = bezier();
+        assert_eq!(curve.degree(), 4);
+        // bernstein form at t
+        let t: f64 = 0.35;
+        let binomial = [1.0, 4.0, 6.0, 4.0, 1.0];
+        let (mut x, mut y) = (0.0, 0.0);
+        for (i, q) in curve.control_points().iter().enumerate() {
+            let b = binomial[i] * t.powi(i as i32) * (1.0 - t).powi(4 - i as i32);
+            x += b * q.x();
+            y += b * q.y();
+        }
+        assert!(curve.point(t).distance(&Cartesian2D::new(x, y)) < 1e-14);
+
+        let h = 1e-6;
+        let (a, b) = (curve.point(t - h), curve.point(t + h));
+        let d = curve.first_derivative(t);
+        assert!((d.x() - (b.x() - a.x()) / (2.0 * h)).abs() < 1e-7 && (d.y() - (b.y() - a.y()) / (2.0 * h)).abs() < 1e-7);
+        assert_eq!(curve.first_derivative(0.0), Cartesian2D::new(4.0, 12.0));
+        assert_eq!(BezierCurve::new(vec![Cartesian2D::new(1.0, 1.0)]).unwrap().derivative().point(0.5), Cartesian2D::new(0.0, 0.0));
+        assert!(BezierCurve::new(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_bezier_subdivision_and_elevation() {
+        let curve = bezier();
+        let (first, second) = curve.subdivide(0.3).unwrap();
+        let elevated = curve.elevate_degree();
+        assert_eq!(elevated.degree(), 5);
+        for k in 0..=20 {
+            let t = k as f64 / 20.0;
+            assert!(first.point(t).distance(&curve.point(0.3 * t)) < 1e-13);
+            assert!(second.point(t).distance(&curve.point(0.3 + 0.7 * t)) < 1e-13);
+            assert!(elevated.point(t).distance(&curve.point(t)) < 1e-13);
+        }
+        assert!(curve.subdivide(1.5).is_err());
+    }
+
+    #[test]
+    fn test_bezier_bspline_conversion() {
+        let curve = bezier();
+        let spline = curve.to_bspline();
+        assert!(spline.point(0.61).distance(&curve.point(0.61)) < 1e-13);
+        assert_eq!(BezierCurve::from_bspline(&spline).unwrap(), vec![curve]);
+
+        // one piece per knot span, each over its own [0, 1]
+        let spline = cubic_spline();
+        let pieces = BezierCurve::from_bspline(&spline).unwrap();
+        assert_eq!(pieces.len(), 3);
+        for (piece, (a, b)) in pieces.iter().zip([(0.0, 0.3), (0.3, 0.5), (0.5, 1.0)]) {
+            assert_eq!(piece.degree(), 3);
+            for k in 0..=10 {
+                let t = k as f64 / 10.0;
+                assert!(piece.point(t).distance(&spline.point(a + t * (b - a))) < 1e-12);
+            }
+        }
+
+        let unclamped = BSpline::new(spline.control_points().to_vec(), (0..10).map(|k| k as f64).collect(), 3).unwrap();
+        assert!(BezierCurve::from_bspline(&unclamped).is_err());
+    }
+
+    fn on_both(hit: &Intersection, a: &impl ParametricCurve2D, b: &impl ParametricCurve2D) -> bool {
+        a.point(hit.first()).distance(&hit.point()) < 1e-8 && b.point(hit.second()).distance(&hit.point()) < 1e-8
+    }
+
+    #[test]
+    fn test_intersect_circle_with_segment_and_polyline() {
+        let circle = Nurbs::circle(Cartesian2D::new(0.0, 0.0), 1.0).unwrap();
+        let segment = Segment2D::new(Cartesian2D::new(-2.0, 0.5), Cartesian2D::new(2.0, 0.5));
+        let hits = intersect(&segment, &circle);
+        assert_eq!(hits.len(), 2);
+        let x = 0.75_f64.sqrt();
+        assert!(hits[0].point().distance(&Cartesian2D::new(-x, 0.5)) < 1e-9);
+        assert!(hits[1].point().distance(&Cartesian2D::new(x, 0.5)) < 1e-9);
+        assert!(hits.iter().all(|hit| on_both(hit, &segment, &circle)));
+
+        // a square a little smaller than the circle's bounding box cuts it twice per side
+        let square = [(-0.9, -0.9), (0.9, -0.9), (0.9, 0.9), (-0.9, 0.9), (-0.9, -0.9)];
+        let polyline = Polyline2D::new(square.iter().map(|&(x, y)| Cartesian2D::new(x, y)).collect()).unwrap();
+        let hits = intersect(&polyline, &circle);
+        assert_eq!(hits.len(), 8);
+        assert!(hits.iter().all(|hit| on_both(hit, &polyline, &circle)));
+        assert!(hits.windows(2).all(|w| w[0].first() < w[1].first()));
+    }
+
+    #[test]
+    fn test_intersect_lines_and_touching_curves() {
+        // y = x^2 against y = 1
+        let parabola = PolynomialCurve::new(Polynomial::new(vec![1.0, 0.0, 0.0]), -2.0, 2.0).unwrap();
+        let hits = intersect_line(&parabola, Cartesian2D::new(3.0, 1.0), Cartesian2D::new(-2.0, 0.0)).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!((hits[0].first() + 1.0).abs() < 1e-9 && (hits[1].first() - 1.0).abs() < 1e-9);
+        assert!((hits[0].second() - 2.0).abs() < 1e-9 && (hits[1].second() - 1.0).abs() < 1e-9);
+        assert!(intersect_line(&parabola, Cartesian2D::new(0.0, 0.0), Cartesian2D::new(0.0, 0.0)).is_err());
+
+        // y = x^3 - x crosses its chord three times
+        let cubic = PolynomialCurve::new(Polynomial::new(vec![1.0, 0.0, -1.0, 0.0]), -2.0, 2.0).unwrap();
+        let axis = Segment2D::new(Cartesian2D::new(-2.0, 0.0), Cartesian2D::new(2.0, 0.0));
+        let roots: Vec<f64> = intersect(&cubic, &axis).iter().map(|hit| hit.first()).collect();
+        assert_eq!(roots.len(), 3);
+        for (root, expected) in roots.iter().zip([-1.0, 0.0, 1.0]) {
+            assert!((root - expected).abs() < 1e-9);
+        }
+
+        // a line resting on top of a circle touches it once
+        let circle = Nurbs::circle(Cartesian2D::new(0.0, 0.0), 1.0).unwrap();
+        let hits = intersect_line(&circle, Cartesian2D::new(0.0, 1.0), Cartesian2D::new(1.0, 0.0)).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].point().distance(&Cartesian2D::new(0.0, 1.0)) < 1e-12);
+        for (x0, y) in [(0.3, -1.0), (-2.0, 1.0)] {
+            let hits = intersect_line(&circle, Cartesian2D::new(y, x0), Cartesian2D::new(0.0, 1.0)).unwrap();
+            assert_eq!(hits.len(), 1);
+            assert!(hits[0].point().distance(&Cartesian2D::new(y, 0.0)) < 1e-12);
+        }
+        // and two circles touching on the outside
+        let small = Nurbs::circle(Cartesian2D::new(1.5, 0.0), 0.5).unwrap();
+        let hits = intersect(&circle, &small);
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].point().distance(&Cartesian2D::new(1.0, 0.0)) < 1e-12);
+
+        // boundaries meeting end to end at a corner
+        let first = Segment2D::new(Cartesian2D::new(0.0, 0.0), Cartesian2D::new(1.0, 0.0));
+        let second = cubic_spline();
+        let corner = Segment2D::new(Cartesian2D::new(5.0, 2.0), Cartesian2D::new(5.0, 4.0));
+        let hits = intersect(&second, &corner);
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].first(), hits[0].second()), (1.0, 0.0));
+        assert!(intersect(&first, &corner).is_empty());
+    }
+
+    #[test]
+    fn test_intersect_splines() {
+        let points: Vec<Cartesian2D> = (0..9).map(|k| Cartesian2D::new(0.5 * k as f64, (k as f64).sin() + 0.5)).collect();
+        let interpolated = CubicSpline::new(points, EndCondition::NotAKnot, Parameterisation::ChordLength).unwrap();
+        let spline = cubic_spline();
+        let hits = intersect(&interpolated, &spline);
+        assert!(!hits.is_empty());
+        assert!(hits.iter().all(|hit| on_both(hit, &interpolated, &spline)));
+
+        // each crossing shows up as a sign change of the height difference on a fine sampling
+        let bezier = BezierCurve::from_bspline(&spline).unwrap();
+        let hits = intersect(&bezier[1], &Segment2D::new(Cartesian2D::new(0.0, 0.3), Cartesian2D::new(6.0, 0.3)));
+        assert!(hits.iter().all(|hit| (hit.point().y() - 0.3).abs() < 1e-9));
+        let changes = (0..1000)
+            .filter(|&k| (bezier[1].point(k as f64 / 1000.0).y() - 0.3).signum() != (bezier[1].point((k + 1) as f64 / 1000.0).y() - 0.3).signum())
+            .count();
+        assert_eq!(hits.len(), changes);
     }
 
     #[test]
@@ -42,7 +42,7 @@ impl<S: Scalar, const ROWS: usize, const COLS: usize> std::fmt::Display for Matr
             for j in 0..COLS {
                 write!(f, " {:?} ", self[(i, j)])?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
@@ -65,7 +65,7 @@ impl<S: Scalar, const ROWS: usize, const COLS: usize> Matrix<S, ROWS, COLS> {
         Matrix { data: vec![value; ROWS * COLS], }
     }
 
-    pub fn from_vec(data: &Vec<S>) -> Result<Self, &'static str> {
+    pub fn from_vec(data: &[S]) -> Result<Self, &'static str> {
         if data.len() != ROWS * COLS {
             return Err("vec length does not match matrix dimensions");
         }
@@ -222,28 +222,28 @@ mod tests {
 
     #[test]
     fn test_add() {
-        let m1 = Matrix::<i32, 2, 2>::from_vec(&vec![1, 2, 3, 4]).unwrap();
-        let m2 = Matrix::<i32, 2, 2>::from_vec(&vec![5, 6, 7, 8]).unwrap();
+        let m1 = Matrix::<i32, 2, 2>::from_vec(&[1, 2, 3, 4]).unwrap();
+        let m2 = Matrix::<i32, 2, 2>::from_vec(&[5, 6, 7, 8]).unwrap();
         let result = m1 + m2;
-        let expected = Matrix::<i32, 2, 2>::from_vec(&vec![6, 8, 10, 12]).unwrap();
+        let expected = Matrix::<i32, 2, 2>::from_vec(&[6, 8, 10, 12]).unwrap();
         assert_eq!(result.data, expected.data);
     }
 
     #[test]
     fn test_sub() {
-        let m1 = Matrix::<i32, 2, 2>::from_vec(&vec![5, 6, 7, 8]).unwrap();
-        let m2 = Matrix::<i32, 2, 2>::from_vec(&vec![1, 2, 3, 4]).unwrap();
+        let m1 = Matrix::<i32, 2, 2>::from_vec(&[5, 6, 7, 8]).unwrap();
+        let m2 = Matrix::<i32, 2, 2>::from_vec(&[1, 2, 3, 4]).unwrap();
         let result = m1 - m2;
-        let expected = Matrix::<i32, 2, 2>::from_vec(&vec![4, 4, 4, 4]).unwrap();
+        let expected = Matrix::<i32, 2, 2>::from_vec(&[4, 4, 4, 4]).unwrap();
         assert_eq!(result.data, expected.data);
     }
 
     #[test]
     fn test_mul() {
-        let m1 = Matrix::<i32, 2, 3>::from_vec(&vec![1, 2, 3, 4, 5, 6]).unwrap();
-        let m2 = Matrix::<i32, 3, 2>::from_vec(&vec![7, 8, 9, 10, 11, 12]).unwrap();
+        let m1 = Matrix::<i32, 2, 3>::from_vec(&[1, 2, 3, 4, 5, 6]).unwrap();
+        let m2 = Matrix::<i32, 3, 2>::from_vec(&[7, 8, 9, 10, 11, 12]).unwrap();
         let result = m1 * m2;
-        let expected = Matrix::<i32, 2, 2>::from_vec(&vec![58, 64, 139, 154]).unwrap();
+        let expected = Matrix::<i32, 2, 2>::from_vec(&[58, 64, 139, 154]).unwrap();
         assert_eq!(result.data, expected.data);
     }
 }
\ No newline at end of file
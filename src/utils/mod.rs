@@ -1,4 +1,5 @@
 pub mod geometry;
 pub mod plotting;
 pub mod numerics;
-pub mod matrices;
\ No newline at end of file
+pub mod matrices;
+pub mod stretching;
\ No newline at end of file
@@ -0,0 +1,357 @@
+#![allow(dead_code)]
+
+// small fixed size linear algebra for the 2x2 block systems that come out of the
+// hyperbolic and elliptic grid equations. these live on the stack, unlike matrices::Matrix
+pub type Vec2 = [f64; 2];
+pub type Mat2 = [[f64; 2]; 2];
+
+pub fn mat2_identity() -> Mat2 {
+    [[1.0, 0.0], [0.0, 1.0]]
+}
+
+pub fn mat2_scale(a: &Mat2, s: f64) -> Mat2 {
+    [[a[0][0] * s, a[0][1] * s], [a[1][0] * s, a[1][1] * s]]
+}
+
+pub fn mat2_add(a: &Mat2, b: &Mat2) -> Mat2 {
+    [[a[0][0] + b[0][0], a[0][1] + b[0][1]], [a[1][0] + b[1][0], a[1][1] + b[1][1]]]
+}
+
+pub fn mat2_sub(a: &Mat2, b: &Mat2) -> Mat2 {
+    [[a[0][0] - b[0][0], a[0][1] - b[0][1]], [a[1][0] - b[1][0], a[1][1] - b[1][1]]]
+}
+
+pub fn mat2_mul(a: &Mat2, b: &Mat2) -> Mat2 {
+    [
+        [a[0][0] * b[0][0] + a[0][1] * b[1][0], a[0][0] * b[0][1] + a[0][1] * b[1][1]],
+        [a[1][0] * b[0][0] + a[1][1] * b[1][0], a[1][0] * b[0][1] + a[1][1] * b[1][1]],
+    ]
+}
+
+pub fn mat2_vec(a: &Mat2, v: &Vec2) -> Vec2 {
+    [a[0][0] * v[0] + a[0][1] * v[1], a[1][0] * v[0] + a[1][1] * v[1]]
+}
+
+pub fn mat2_det(a: &Mat2) -> f64 {
+    a[0][0] * a[1][1] - a[0][1] * a[1][0]
+}
+
+pub fn mat2_inverse(a: &Mat2) -> Result<Mat2, &'static str> {
+    let det = mat2_det(a);
+    if det.abs() <= f64::MIN_POSITIVE {
+        return Err("singular 2x2 matrix");
+    }
+    Ok([[a[1][1] / det, -a[0][1] / det], [-a[1][0] / det, a[0][0] / det]])
+}
+
+fn vec2_sub(a: &Vec2, b: &Vec2) -> Vec2 {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+// block thomas algorithm for
+//     lower[i] x[i-1] + diag[i] x[i] + upper[i] x[i+1] = rhs[i]
+// lower[0] and upper[n-1] are ignored
+pub fn solve_block_tridiagonal(lower: &[Mat2], diag: &[Mat2], upper: &[Mat2], rhs: &[Vec2]) -> Result<Vec<Vec2>, &'static str> {
+    let n = diag.len();
+    if lower.len() != n || upper.len() != n || rhs.len() != n {
+        return Err("block tridiagonal system has mismatched lengths");
+    }
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    // forward elimination, storing the modified upper blocks and right hand sides
+    let mut upper_star: Vec<Mat2> = Vec::with_capacity(n);
+    let mut rhs_star: Vec<Vec2> = Vec::with_capacity(n);
+    for i in 0..n {
+        let (pivot, r) = if i == 0 {
+            (diag[0], rhs[0])
+        } else {
+            let pivot = mat2_sub(&diag[i], &mat2_mul(&lower[i], &upper_star[i - 1]));
+            let r = vec2_sub(&rhs[i], &mat2_vec(&lower[i], &rhs_star[i - 1]));
+            (pivot, r)
+        };
+        let pivot_inv = mat2_inverse(&pivot)?;
+        upper_star.push(mat2_mul(&pivot_inv, &upper[i]));
+        rhs_star.push(mat2_vec(&pivot_inv, &r));
+    }
+
+    // back substitution
+    let mut x = rhs_star;
+    for i in (0..n - 1).rev() {
+        let correction = mat2_vec(&upper_star[i], &x[i + 1]);
+        x[i] = vec2_sub(&x[i], &correction);
+    }
+    Ok(x)
+}
+
+// block tridiagonal system with periodic coupling, lower[0] multiplies x[n-1] and
+// upper[n-1] multiplies x[0]. the first n-1 unknowns are solved with the last one
+// treated as a parameter, then the last row fixes it
+pub fn solve_periodic_block_tridiagonal(lower: &[Mat2], diag: &[Mat2], upper: &[Mat2], rhs: &[Vec2]) -> Result<Vec<Vec2>, &'static str> {
+    let n = diag.len();
+    if lower.len() != n || upper.len() != n || rhs.len() != n {
+        return Err("block tridiagonal system has mismatched lengths");
+    }
+    if n < 3 {
+        return Err("periodic block tridiagonal system needs at least three blocks");
+    }
+
+    let m = n - 1;
+    let y = solve_block_tridiagonal(&lower[..m], &diag[..m], &upper[..m], &rhs[..m])?;
+
+    // influence of x[n-1] on the reduced system, one column at a time
+    let mut columns: Vec<Vec<Vec2>> = Vec::with_capacity(2);
+    for c in 0..2 {
+        let mut e = vec![[0.0, 0.0]; m];
+        e[0] = [lower[0][0][c], lower[0][1][c]];
+        e[m - 1][0] += upper[m - 1][0][c];
+        e[m - 1][1] += upper[m - 1][1][c];
+        columns.push(solve_block_tridiagonal(&lower[..m], &diag[..m], &upper[..m], &e)?);
+    }
+    let z = |i: usize| -> Mat2 { [[columns[0][i][0], columns[1][i][0]], [columns[0][i][1], columns[1][i][1]]] };
+
+    // last row: lower x[n-2] + diag x[n-1] + upper x[0] = rhs, with x[i] = y[i] - z[i] x[n-1]
+    let reduced = mat2_sub(
+        &mat2_sub(&diag[m], &mat2_mul(&lower[m], &z(m - 1))),
+        &mat2_mul(&upper[m], &z(0)),
+    );
+    let r = vec2_sub(
+        &vec2_sub(&rhs[m], &mat2_vec(&lower[m], &y[m - 1])),
+        &mat2_vec(&upper[m], &y[0]),
+    );
+    let last = mat2_vec(&mat2_inverse(&reduced)?, &r);
+
+    let mut x: Vec<Vec2> = (0..m).map(|i| vec2_sub(&y[i], &mat2_vec(&z(i), &last))).collect();
+    x.push(last);
+    Ok(x)
+}
+
+// dense stuff //
+// least squares solution of a x = b for a with at least as many rows as columns, by
+// householder qr on a copy. square systems are solved exactly
+pub fn least_squares(a: &[Vec<f64>], b: &[f64]) -> Result<Vec<f64>, &'static str> {
+    let (m, n) = (a.len(), a.first().map_or(0, |row| row.len()));
+    if n == 0 || m < n {
+        return Err("least squares needs at least as many equations as unknowns");
+    }
+    if a.iter().any(|row| row.len() != n) || b.len() != m {
+        return Err("least squares system has mismatched sizes");
+    }
+
+    // columns of a, reduced in place to r
+    let mut columns: Vec<Vec<f64>> = (0..n).map(|j| a.iter().map(|row| row[j]).collect()).collect();
+    let mut b = b.to_vec();
+    let scale = columns.iter().map(|c| c.iter().map(|v| v * v).sum::<f64>().sqrt()).fold(0.0, f64::max);
+
+    for k in 0..n {
+        let norm = columns[k][k..].iter().map(|v| v * v).sum::<f64>().sqrt();
+        if norm <= LEAST_SQUARES_RANK_TOL * scale {
+            return Err("least squares system is rank deficient");
+        }
+        let alpha = if columns[k][k] > 0.0 { -norm } else { norm };
+        let mut v = columns[k][k..].to_vec();
+        v[0] -= alpha;
+        let v_norm2: f64 = v.iter().map(|x| x * x).sum();
+
+        let reflect = |x: &mut [f64]| {
+            let dot: f64 = v.iter().zip(x.iter()).map(|(a, b)| a * b).sum();
+            let factor = 2.0 * dot / v_norm2;
+            for (x, v) in x.iter_mut().zip(v.iter()) {
+                *x -= factor * v;
+            }
+        };
+        for column in columns[k + 1..].iter_mut() {
+            reflect(&mut column[k..]);
+        }
+        reflect(&mut b[k..]);
+        columns[k][k] = alpha;
+    }
+
+    let mut x = vec![0.0; n];
+    for k in (0..n).rev() {
+        let sum: f64 = (k + 1..n).map(|j| columns[j][k] * x[j]).sum();
+        x[k] = (b[k] - sum) / columns[k][k];
+    }
+    Ok(x)
+}
+
+const LEAST_SQUARES_RANK_TOL: f64 = 1e-13;
+
+// quadrature stuff //
+// adaptive gauss legendre: halves an interval until the two halves agree with the whole
+// to within tol relative to the running total, so kinks only refine where they are
+pub fn integrate(f: impl Fn(f64) -> f64, a: f64, b: f64, tol: f64) -> f64 {
+    let whole = gauss_legendre(&f, a, b);
+    adaptive(&f, a, b, whole, tol, MAX_QUADRATURE_DEPTH)
+}
+
+fn adaptive(f: &impl Fn(f64) -> f64, a: f64, b: f64, whole: f64, tol: f64, depth: usize) -> f64 {
+    let mid = 0.5 * (a + b);
+    let (left, right) = (gauss_legendre(f, a, mid), gauss_legendre(f, mid, b));
+    if depth == 0 || (left + right - whole).abs() <= tol * (left + right).abs().max(f64::MIN_POSITIVE) {
+        return left + right;
+    }
+    adaptive(f, a, mid, left, tol, depth - 1) + adaptive(f, mid, b, right, tol, depth - 1)
+}
+
+// five point rule on [a, b]
+fn gauss_legendre(f: &impl Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+    const NODES: [(f64, f64); 5] = [
+        (0.0, 0.568_888_888_888_888_9),
+        (-0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+        (0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+        (-0.906_179_845_938_664, 0.236_926_885_056_189_1),
+        (0.906_179_845_938_664, 0.236_926_885_056_189_1),
+    ];
+    let (centre, half) = (0.5 * (a + b), 0.5 * (b - a));
+    half * NODES.iter().map(|(x, w)| w * f(centre + half * x)).sum::<f64>()
+}
+
+const MAX_QUADRATURE_DEPTH: usize = 30;
+
+// root finding stuff //
+// bisection on a bracket [lo, hi] where f changes sign, stops once the bracket is
+// narrower than tol relative to its position
+pub fn bisect(f: impl Fn(f64) -> f64, lo: f64, hi: f64, tol: f64) -> Result<f64, &'static str> {
+    let (mut lo, mut hi) = (lo, hi);
+    let (mut f_lo, f_hi) = (f(lo), f(hi));
+    if f_lo == 0.0 {
+        return Ok(lo);
+    }
+    if f_hi == 0.0 {
+        return Ok(hi);
+    }
+    if f_lo.signum() == f_hi.signum() {
+        return Err("root is not bracketed");
+    }
+
+    for _ in 0..MAX_BISECTIONS {
+        let mid = 0.5 * (lo + hi);
+        let f_mid = f(mid);
+        if f_mid == 0.0 || (hi - lo).abs() <= tol * mid.abs().max(1.0) {
+            return Ok(mid);
+        }
+        if f_mid.signum() == f_lo.signum() {
+            lo = mid;
+            f_lo = f_mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(0.5 * (lo + hi))
+}
+
+// newton iteration from x0, fails if the derivative vanishes or it runs out of iterations
+pub fn newton(f: impl Fn(f64) -> f64, df: impl Fn(f64) -> f64, x0: f64, tol: f64, max_iterations: usize) -> Result<f64, &'static str> {
+    let mut x = x0;
+    for _ in 0..max_iterations {
+        let slope = df(x);
+        if slope == 0.0 || !slope.is_finite() {
+            return Err("newton iteration hit a zero derivative");
+        }
+        let step = f(x) / slope;
+        x -= step;
+        if !x.is_finite() {
+            return Err("newton iteration diverged");
+        }
+        if step.abs() <= tol * x.abs().max(1.0) {
+            return Ok(x);
+        }
+    }
+    Err("newton iteration did not converge")
+}
+
+const MAX_BISECTIONS: usize = 200;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1e-10;
+
+    fn multiply(lower: &[Mat2], diag: &[Mat2], upper: &[Mat2], x: &[Vec2], periodic: bool) -> Vec<Vec2> {
+        let n = x.len();
+        (0..n)
+            .map(|i| {
+                let mut r = mat2_vec(&diag[i], &x[i]);
+                if i > 0 || periodic {
+                    let l = mat2_vec(&lower[i], &x[(i + n - 1) % n]);
+                    r = [r[0] + l[0], r[1] + l[1]];
+                }
+                if i < n - 1 || periodic {
+                    let u = mat2_vec(&upper[i], &x[(i + 1) % n]);
+                    r = [r[0] + u[0], r[1] + u[1]];
+                }
+                r
+            })
+            .collect()
+    }
+
+    fn system(n: usize) -> (Vec<Mat2>, Vec<Mat2>, Vec<Mat2>, Vec<Vec2>) {
+        let lower: Vec<Mat2> = (0..n).map(|i| [[-1.0, 0.3], [0.1 * i as f64, -1.0]]).collect();
+        let diag: Vec<Mat2> = (0..n).map(|i| [[4.0 + 0.1 * i as f64, 0.5], [-0.5, 4.0]]).collect();
+        let upper: Vec<Mat2> = (0..n).map(|_| [[-1.0, -0.2], [0.4, -1.0]]).collect();
+        let x: Vec<Vec2> = (0..n).map(|i| [i as f64, (i as f64).sin()]).collect();
+        (lower, diag, upper, x)
+    }
+
+    #[test]
+    fn test_block_tridiagonal() {
+        let (lower, diag, upper, expected) = system(8);
+        let rhs = multiply(&lower, &diag, &upper, &expected, false);
+        let x = solve_block_tridiagonal(&lower, &diag, &upper, &rhs).unwrap();
+        for (a, b) in x.iter().zip(expected.iter()) {
+            assert!((a[0] - b[0]).abs() < TOL && (a[1] - b[1]).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_periodic_block_tridiagonal() {
+        let (lower, diag, upper, expected) = system(9);
+        let rhs = multiply(&lower, &diag, &upper, &expected, true);
+        let x = solve_periodic_block_tridiagonal(&lower, &diag, &upper, &rhs).unwrap();
+        for (a, b) in x.iter().zip(expected.iter()) {
+            assert!((a[0] - b[0]).abs() < TOL && (a[1] - b[1]).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_singular_inverse() {
+        assert!(mat2_inverse(&[[1.0, 2.0], [2.0, 4.0]]).is_err());
+    }
+
+    #[test]
+    fn test_least_squares() {
+        // straight line through points scattered evenly either side of y = 2x + 1
+        let xs = [0.0, 1.0, 2.0, 3.0];
+        let a: Vec<Vec<f64>> = xs.iter().map(|&x| vec![x, 1.0]).collect();
+        let b: Vec<f64> = xs.iter().enumerate().map(|(k, &x)| 2.0 * x + 1.0 + if k % 3 == 0 { 0.1 } else { -0.1 }).collect();
+        let x = least_squares(&a, &b).unwrap();
+        assert!((x[0] - 2.0).abs() < TOL && (x[1] - 1.0).abs() < TOL);
+
+        let square = least_squares(&[vec![0.0, 2.0], vec![3.0, 1.0]], &[4.0, 5.0]).unwrap();
+        assert!((square[0] - 1.0).abs() < TOL && (square[1] - 2.0).abs() < TOL);
+        assert!(least_squares(&[vec![1.0, 2.0], vec![2.0, 4.0]], &[1.0, 2.0]).is_err());
+        assert!(least_squares(&[vec![1.0, 2.0]], &[1.0]).is_err());
+    }
+
+    #[test]
+    fn test_integrate() {
+        assert!((integrate(|x| x.powi(7), 0.0, 2.0, 1e-12) - 32.0).abs() < TOL);
+        assert!((integrate(f64::sin, 0.0, std::f64::consts::PI, 1e-12) - 2.0).abs() < TOL);
+        // a kink in the middle of the interval
+        assert!((integrate(|x: f64| (x - 0.3).abs(), 0.0, 1.0, 1e-12) - 0.29).abs() < TOL);
+    }
+
+    #[test]
+    fn test_bisect_and_newton() {
+        let f = |x: f64| x * x - 2.0;
+        let root = bisect(f, 0.0, 2.0, 1e-14).unwrap();
+        assert!((root - 2.0_f64.sqrt()).abs() < 1e-12);
+        assert!(bisect(f, 2.0, 3.0, 1e-14).is_err());
+
+        let root = newton(f, |x| 2.0 * x, 1.0, 1e-14, 50).unwrap();
+        assert!((root - 2.0_f64.sqrt()).abs() < 1e-12);
+    }
+}
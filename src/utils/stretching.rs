@@ -0,0 +1,327 @@
+#![allow(dead_code)]
+
+use super::geometry::{invert_integral, Cartesian2D, ParametricCurve2D};
+use super::numerics::bisect;
+
+// 1d point distribution stuff //
+// every distribution maps the uniform index coordinate xi = k / (n - 1) onto s in [0, 1].
+// the one-sided functions cluster points towards s = 0, use mirror() to cluster at s = 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stretching {
+    Uniform,
+    // each spacing is ratio times the previous one
+    Geometric { ratio: f64 },
+    // s = 1 + tanh(delta (xi - 1)) / tanh(delta)
+    Tanh { delta: f64 },
+    // s = sinh(delta xi) / sinh(delta)
+    Sinh { delta: f64 },
+    // two-sided stretching of vinokur (1983) from the wanted spacing at each end, given as
+    // fractions of the total length. the end spacings are matched to first order
+    Vinokur { start: f64, end: f64 },
+    // roberts transformation with beta > 1, clustering gets stronger as beta -> 1.
+    // alpha = 0 clusters at the start only, alpha = 0.5 clusters at both ends
+    Roberts { beta: f64, alpha: f64 },
+}
+
+impl Stretching {
+    const TOL: f64 = 1e-14;
+
+    // geometric ratio giving a first spacing of `spacing` (a fraction of the total length)
+    pub fn geometric_from_spacing(spacing: f64, n: usize) -> Result<Self, &'static str> {
+        let intervals = check_spacing(spacing, n)?;
+        let uniform = 1.0 / intervals as f64;
+        if (spacing - uniform).abs() <= Self::TOL {
+            return Ok(Stretching::Geometric { ratio: 1.0 });
+        }
+
+        let residual = |ratio: f64| geometric_first_spacing(ratio, intervals) - spacing;
+        let ratio = if spacing < uniform {
+            let mut hi = 2.0;
+            while residual(hi) > 0.0 {
+                hi *= 2.0;
+                if hi > 1e6 {
+                    return Err("first spacing is too small for a geometric distribution");
+                }
+            }
+            bisect(residual, 1.0 + 1e-12, hi, Self::TOL)?
+        } else {
+            bisect(residual, 1e-12, 1.0 - 1e-12, Self::TOL)?
+        };
+        Ok(Stretching::Geometric { ratio })
+    }
+
+    pub fn tanh_from_spacing(spacing: f64, n: usize) -> Result<Self, &'static str> {
+        let delta = solve_clustering(spacing, n, 1e-8, 100.0, |delta| Stretching::Tanh { delta })?;
+        Ok(Stretching::Tanh { delta })
+    }
+
+    pub fn sinh_from_spacing(spacing: f64, n: usize) -> Result<Self, &'static str> {
+        let delta = solve_clustering(spacing, n, 1e-8, 500.0, |delta| Stretching::Sinh { delta })?;
+        Ok(Stretching::Sinh { delta })
+    }
+
+    // beta is found through ln(beta - 1) so the bracket covers very strong clustering
+    pub fn roberts_from_spacing(spacing: f64, n: usize, alpha: f64) -> Result<Self, &'static str> {
+        check_alpha(alpha)?;
+        let log_beta = solve_clustering(spacing, n, 30.0, -30.0, |log_beta| Stretching::Roberts { beta: 1.0 + log_beta.exp(), alpha })?;
+        Ok(Stretching::Roberts { beta: 1.0 + log_beta.exp(), alpha })
+    }
+
+    pub fn distribute(&self, n: usize) -> Result<Vec<f64>, &'static str> {
+        if n < 2 {
+            return Err("a distribution needs at least two points");
+        }
+        self.check()?;
+
+        let intervals = (n - 1) as f64;
+        let map = self.mapping(n)?;
+        let mut s: Vec<f64> = (0..n).map(|k| map(k as f64 / intervals)).collect();
+        s[0] = 0.0;
+        s[n - 1] = 1.0;
+        if s.iter().any(|t| !t.is_finite()) || s.windows(2).any(|pair| pair[1] <= pair[0]) {
+            return Err("distribution is not strictly increasing");
+        }
+        Ok(s)
+    }
+
+    // the distribution laid out along the straight segment from a to b
+    pub fn distribute_between(&self, a: Cartesian2D, b: Cartesian2D, n: usize) -> Result<Vec<Cartesian2D>, &'static str> {
+        let s = self.distribute(n)?;
+        Ok(s.iter()
+            .map(|&t| Cartesian2D::new(a.x() + t * (b.x() - a.x()), a.y() + t * (b.y() - a.y())))
+            .collect())
+    }
+
+    // the distribution laid out by arc length along a curve, from the start of its domain
+    pub fn distribute_along(&self, curve: &(impl ParametricCurve2D + ?Sized), n: usize) -> Result<Vec<Cartesian2D>, &'static str> {
+        let s = self.distribute(n)?;
+        let parameters = invert_integral(|t| curve.speed(t), &curve.breakpoints(), &s)?;
+        Ok(parameters.into_iter().map(|t| curve.point(t)).collect())
+    }
+
+    pub fn first_spacing(&self, n: usize) -> Result<f64, &'static str> {
+        let s = self.distribute(n)?;
+        Ok(s[1] - s[0])
+    }
+
+    pub fn last_spacing(&self, n: usize) -> Result<f64, &'static str> {
+        let s = self.distribute(n)?;
+        Ok(s[n - 1] - s[n - 2])
+    }
+
+    fn check(&self) -> Result<(), &'static str> {
+        match *self {
+            Stretching::Uniform => Ok(()),
+            Stretching::Geometric { ratio } if ratio > 0.0 => Ok(()),
+            Stretching::Geometric { .. } => Err("geometric ratio must be positive"),
+            Stretching::Tanh { delta } | Stretching::Sinh { delta } if delta > 0.0 => Ok(()),
+            Stretching::Tanh { .. } | Stretching::Sinh { .. } => Err("stretching factor must be positive"),
+            Stretching::Vinokur { start, end } if start > 0.0 && end > 0.0 && start < 1.0 && end < 1.0 => Ok(()),
+            Stretching::Vinokur { .. } => Err("vinokur end spacings must be between 0 and 1"),
+            Stretching::Roberts { beta, alpha } if beta > 1.0 => check_alpha(alpha),
+            Stretching::Roberts { .. } => Err("roberts beta must be greater than 1"),
+        }
+    }
+
+    // the map from xi to s for n points. vinokur has to solve for its parameter first, and
+    // depends on n since its end spacings are fractions of the total length
+    fn mapping(&self, n: usize) -> Result<Box<dyn Fn(f64) -> f64>, &'static str> {
+        let map: Box<dyn Fn(f64) -> f64> = match *self {
+            Stretching::Uniform => Box::new(|xi| xi),
+            Stretching::Geometric { ratio } => {
+                if (ratio - 1.0).abs() <= Self::TOL {
+                    Box::new(|xi| xi)
+                } else {
+                    // continuous form of the geometric series, exact at the nodes
+                    let rate = (n - 1) as f64 * ratio.ln();
+                    Box::new(move |xi| (xi * rate).exp_m1() / rate.exp_m1())
+                }
+            }
+            Stretching::Tanh { delta } => Box::new(move |xi| 1.0 + (delta * (xi - 1.0)).tanh() / delta.tanh()),
+            Stretching::Sinh { delta } => Box::new(move |xi| (delta * xi).sinh() / delta.sinh()),
+            Stretching::Roberts { beta, alpha } => {
+                let ratio = (beta + 1.0) / (beta - 1.0);
+                // written with xi -> 1 - xi so that alpha = 0 clusters at the start
+                Box::new(move |xi| {
+                    let eta = 1.0 - xi;
+                    let power = ratio.powf((eta - alpha) / (1.0 - alpha));
+                    let s = ((2.0 * alpha + beta) * power + 2.0 * alpha - beta) / ((2.0 * alpha + 1.0) * (1.0 + power));
+                    1.0 - s
+                })
+            }
+            Stretching::Vinokur { start, end } => vinokur(start, end, n - 1)?,
+        };
+        Ok(map)
+    }
+}
+
+// reverses a distribution on [0, 1] so clustering at the start moves to the end
+pub fn mirror(s: &[f64]) -> Vec<f64> {
+    s.iter().rev().map(|t| 1.0 - t).collect()
+}
+
+fn check_spacing(spacing: f64, n: usize) -> Result<usize, &'static str> {
+    if n < 2 {
+        return Err("a distribution needs at least two points");
+    }
+    if spacing <= 0.0 || spacing >= 1.0 {
+        return Err("spacing must be a fraction of the total length between 0 and 1");
+    }
+    Ok(n - 1)
+}
+
+fn check_alpha(alpha: f64) -> Result<(), &'static str> {
+    if alpha == 0.0 || alpha == 0.5 {
+        Ok(())
+    } else {
+        Err("roberts alpha must be 0 (one end) or 0.5 (both ends)")
+    }
+}
+
+fn geometric_first_spacing(ratio: f64, intervals: usize) -> f64 {
+    (ratio - 1.0) / (ratio.powi(intervals as i32) - 1.0)
+}
+
+// finds the parameter in [weak, strong] of a clustering function that gives the wanted
+// first spacing. weak should give nearly uniform spacing and strong very tight spacing
+fn solve_clustering(
+    spacing: f64,
+    n: usize,
+    weak: f64,
+    strong: f64,
+    make: impl Fn(f64) -> Stretching,
+) -> Result<f64, &'static str> {
+    let intervals = check_spacing(spacing, n)?;
+    if spacing >= 1.0 / intervals as f64 {
+        return Err("this stretching can only cluster, spacing must be below the uniform spacing");
+    }
+
+    let residual = |parameter: f64| -> f64 {
+        let map = make(parameter).mapping(n).expect("clustering functions have closed form maps");
+        map(1.0 / intervals as f64) - spacing
+    };
+    if residual(strong) > 0.0 {
+        return Err("first spacing is too small for this stretching function");
+    }
+    bisect(residual, weak, strong, Stretching::TOL)
+}
+
+// s = u / (a + (1 - a) u) with u a symmetric tanh (or tan) stretching, where a and the
+// strength of u are set from the slopes ds/dxi = spacing * intervals wanted at each end
+fn vinokur(start: f64, end: f64, intervals: usize) -> Result<Box<dyn Fn(f64) -> f64>, &'static str> {
+    let intervals = intervals as f64;
+    let a = (end / start).sqrt();
+    let b = 1.0 / (intervals * (start * end).sqrt());
+
+    let u: Box<dyn Fn(f64) -> f64> = if b > 1.0 + 1e-6 {
+        let y = bisect(|y: f64| y.sinh() / y - b, 1e-8, 750.0, 1e-15)?;
+        Box::new(move |xi| 0.5 * (1.0 + (y * (xi - 0.5)).tanh() / (0.5 * y).tanh()))
+    } else if b < 1.0 - 1e-6 {
+        let y = bisect(|y: f64| y.sin() / y - b, 1e-8, std::f64::consts::PI - 1e-12, 1e-15)?;
+        Box::new(move |xi| 0.5 * (1.0 + (y * (xi - 0.5)).tan() / (0.5 * y).tan()))
+    } else {
+        Box::new(|xi| xi)
+    };
+
+    Ok(Box::new(move |xi| {
+        let u = u(xi);
+        u / (a + (1.0 - a) * u)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1e-9;
+
+    fn check_monotone(s: &[f64]) {
+        assert_eq!(s[0], 0.0);
+        assert_eq!(*s.last().unwrap(), 1.0);
+        assert!(s.windows(2).all(|pair| pair[1] > pair[0]));
+    }
+
+    #[test]
+    fn test_uniform() {
+        let s = Stretching::Uniform.distribute(5).unwrap();
+        assert_eq!(s, vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn test_geometric_ratio() {
+        let s = Stretching::Geometric { ratio: 2.0 }.distribute(4).unwrap();
+        // spacings 1/7, 2/7, 4/7
+        let expected = [0.0, 1.0 / 7.0, 3.0 / 7.0, 1.0];
+        for (a, b) in s.iter().zip(expected.iter()) {
+            assert!((a - b).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_solve_for_first_spacing() {
+        let n = 41;
+        let spacing = 1e-4;
+        let stretchings = [
+            Stretching::geometric_from_spacing(spacing, n).unwrap(),
+            Stretching::tanh_from_spacing(spacing, n).unwrap(),
+            Stretching::sinh_from_spacing(spacing, n).unwrap(),
+            Stretching::roberts_from_spacing(spacing, n, 0.0).unwrap(),
+            Stretching::roberts_from_spacing(spacing, n, 0.5).unwrap(),
+        ];
+        for stretching in stretchings.iter() {
+            let s = stretching.distribute(n).unwrap();
+            check_monotone(&s);
+            assert!((s[1] - spacing).abs() < 1e-6 * spacing, "{:?} gives {}", stretching, s[1]);
+        }
+
+        // roberts with alpha = 0.5 is symmetric
+        let s = stretchings[4].distribute(n).unwrap();
+        assert!((s[n - 1] - s[n - 2] - spacing).abs() < 1e-6 * spacing);
+    }
+
+    #[test]
+    fn test_geometric_coarsening() {
+        // a first spacing above uniform gives a ratio below one
+        let n = 11;
+        match Stretching::geometric_from_spacing(0.2, n).unwrap() {
+            Stretching::Geometric { ratio } => assert!(ratio < 1.0),
+            _ => unreachable!(),
+        }
+        assert!(Stretching::tanh_from_spacing(0.2, n).is_err());
+    }
+
+    #[test]
+    fn test_vinokur_end_spacings() {
+        let n = 201;
+        // both tighter than uniform (tanh branch) and one end coarser (tan branch)
+        for (ds0, ds1) in [(1e-3, 2e-3), (1e-3, 2e-2)] {
+            let s = Stretching::Vinokur { start: ds0, end: ds1 }.distribute(n).unwrap();
+            check_monotone(&s);
+            assert!((s[1] - ds0).abs() < 0.05 * ds0, "start spacing {}", s[1]);
+            assert!((s[n - 1] - s[n - 2] - ds1).abs() < 0.05 * ds1, "end spacing {}", s[n - 1] - s[n - 2]);
+        }
+    }
+
+    #[test]
+    fn test_distribute_along_curve() {
+        use crate::utils::geometry::Nurbs;
+        // a half circle of radius 2, where arc length is 2 theta
+        let arc = Nurbs::arc(Cartesian2D::new(0.0, 0.0), 2.0, 0.0, std::f64::consts::PI).unwrap();
+        let stretching = Stretching::Tanh { delta: 2.0 };
+        let s = stretching.distribute(9).unwrap();
+        let points = stretching.distribute_along(&arc, 9).unwrap();
+        for (p, s) in points.iter().zip(s.iter()) {
+            let theta = p.y().atan2(p.x());
+            assert!((theta - s * std::f64::consts::PI).abs() < 1e-10);
+        }
+        assert!(points[8].distance(&Cartesian2D::new(-2.0, 0.0)) < 1e-14);
+    }
+
+    #[test]
+    fn test_mirror() {
+        let s = Stretching::Tanh { delta: 3.0 }.distribute(11).unwrap();
+        let m = mirror(&s);
+        check_monotone(&m);
+        assert!((m[10] - m[9] - s[1]).abs() < TOL);
+    }
+}